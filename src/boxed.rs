@@ -0,0 +1,86 @@
+use crate::IndexedState;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// An object safe form of `IndexedState`, allowing `Stateful` structures of different types
+/// to be stored behind a `Box<dyn DynIndexedState<I, O, A>>`.
+///
+/// Every `IndexedState` implements this trait, and the boxed trait objects implement `IndexedState`.
+/// Adding `+ Send` to the trait object allows it to be sent across threads when the structure is `Send`.
+pub trait DynIndexedState<Input, Output, Value> {
+    /// Given an input state, runs the boxed structure and returns the value of `A` and the output state `O`.
+    fn run_boxed(self: Box<Self>, state: Input) -> (Value, Output);
+}
+
+impl<Input, Output, Value, First> DynIndexedState<Input, Output, Value> for First
+where
+    First: IndexedState<Input, Output, Value>,
+{
+    fn run_boxed(self: Box<Self>, state: Input) -> (Value, Output) {
+        (*self).run(state)
+    }
+}
+
+/// A boxed `Stateful` structure with its type erased, created by `boxed`.
+pub type BoxIndexedState<'a, Input, Output, Value> =
+    Box<dyn DynIndexedState<Input, Output, Value> + 'a>;
+
+impl<'a, Input, Output, Value> IndexedState<Input, Output, Value>
+    for BoxIndexedState<'a, Input, Output, Value>
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        self.run_boxed(state)
+    }
+}
+
+/// A boxed `Stateful` structure with its type erased that can be sent across threads, created by `boxed_send`.
+pub type BoxSendIndexedState<'a, Input, Output, Value> =
+    Box<dyn DynIndexedState<Input, Output, Value> + Send + 'a>;
+
+impl<'a, Input, Output, Value> IndexedState<Input, Output, Value>
+    for BoxSendIndexedState<'a, Input, Output, Value>
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        self.run_boxed(state)
+    }
+}
+
+/// Runs each step in order, threading the state through them.
+///
+/// The steps are usually boxed with `boxed`, so steps of different types can be chosen at runtime.
+pub fn compose_all<State, Step>(steps: Vec<Step>) -> ComposeAll<Step>
+where
+    Step: IndexedState<State, State, ()>,
+{
+    ComposeAll { steps }
+}
+
+#[derive(Clone)]
+pub struct ComposeAll<Step> {
+    steps: Vec<Step>,
+}
+
+impl<Step> fmt::Debug for ComposeAll<Step>
+where
+    Step: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComposeAll")
+            .field("steps", &self.steps)
+            .finish()
+    }
+}
+
+impl<State, Step> IndexedState<State, State, ()> for ComposeAll<Step>
+where
+    Step: IndexedState<State, State, ()>,
+{
+    fn run(self, state: State) -> ((), State) {
+        let state = self
+            .steps
+            .into_iter()
+            .fold(state, |state, step| step.execute(state));
+        ((), state)
+    }
+}
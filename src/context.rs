@@ -0,0 +1,113 @@
+use crate::{Closure, TryIndexedState};
+use core::fmt;
+
+/// Combinators that attach `anyhow` context to the errors of fallible `Stateful` structures.
+pub trait Context<Input, Output, Value>: TryIndexedState<Input, Output, Value>
+where
+    Self::Error: Into<anyhow::Error>,
+{
+    /// Wraps the error of this structure with `context`, like `anyhow::Context::context`.
+    fn context<Message>(self, context: Message) -> WithContext<Self, Message>
+    where
+        Message: fmt::Display + Send + Sync + 'static,
+    {
+        WithContext {
+            first: self,
+            context,
+        }
+    }
+
+    /// Like `context`, only calling `context` to create the message when this structure fails.
+    fn with_context<Contextual, Message>(
+        self,
+        context: Contextual,
+    ) -> WithContextLazy<Self, Contextual>
+    where
+        Contextual: FnOnce() -> Message,
+        Message: fmt::Display + Send + Sync + 'static,
+    {
+        WithContextLazy {
+            first: self,
+            context,
+        }
+    }
+}
+
+impl<Input, Output, Value, First> Context<Input, Output, Value> for First
+where
+    First: TryIndexedState<Input, Output, Value>,
+    First::Error: Into<anyhow::Error>,
+{
+}
+
+#[derive(Clone, Copy)]
+pub struct WithContext<First, Message> {
+    first: First,
+    context: Message,
+}
+
+impl<First, Message> fmt::Debug for WithContext<First, Message>
+where
+    First: fmt::Debug,
+    Message: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithContext")
+            .field("first", &self.first)
+            .field("context", &self.context)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First, Message> TryIndexedState<Input, Output, Value>
+    for WithContext<First, Message>
+where
+    First: TryIndexedState<Input, Output, Value>,
+    First::Error: Into<anyhow::Error>,
+    Message: fmt::Display + Send + Sync + 'static,
+{
+    type Error = anyhow::Error;
+
+    fn try_run(self, state: Input) -> Result<(Value, Output), anyhow::Error> {
+        let context = self.context;
+        self.first
+            .try_run(state)
+            .map_err(|error| error.into().context(context))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct WithContextLazy<First, Contextual> {
+    first: First,
+    context: Contextual,
+}
+
+impl<First, Contextual> fmt::Debug for WithContextLazy<First, Contextual>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithContextLazy")
+            .field("first", &self.first)
+            .field("context", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First, Contextual, Message> TryIndexedState<Input, Output, Value>
+    for WithContextLazy<First, Contextual>
+where
+    First: TryIndexedState<Input, Output, Value>,
+    First::Error: Into<anyhow::Error>,
+    Contextual: FnOnce() -> Message,
+    Message: fmt::Display + Send + Sync + 'static,
+{
+    type Error = anyhow::Error;
+
+    fn try_run(self, state: Input) -> Result<(Value, Output), anyhow::Error> {
+        let context = self.context;
+        self.first
+            .try_run(state)
+            .map_err(|error| error.into().context(context()))
+    }
+}
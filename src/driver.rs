@@ -0,0 +1,59 @@
+use crate::IndexedState;
+
+/// Owns the current state, running `Stateful` structures against it in place.
+#[derive(Clone, Debug)]
+pub struct Driver<State> {
+    state: Option<State>,
+}
+
+impl<State> Driver<State> {
+    /// Creates a driver holding `initial` as the current state.
+    pub fn new(initial: State) -> Self {
+        Driver {
+            state: Some(initial),
+        }
+    }
+
+    /// Runs `transition` against the current state, holding its output state as the current state
+    /// and returning the value of `A`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when a previous transition panicked, as the state is lost.
+    pub fn apply<Value, Transition>(&mut self, transition: Transition) -> Value
+    where
+        Transition: IndexedState<State, State, Value>,
+    {
+        let (a, o) = transition.run(self.take());
+        self.state = Some(o);
+        a
+    }
+
+    /// Like `apply`, consuming the driver so the transition can change the type of the state.
+    pub fn apply_indexed<Output, Value, Transition>(
+        self,
+        transition: Transition,
+    ) -> (Value, Driver<Output>)
+    where
+        Transition: IndexedState<State, Output, Value>,
+    {
+        let (a, o) = transition.run(self.into_state());
+        (a, Driver::new(o))
+    }
+
+    /// Borrows the current state.
+    pub fn state(&self) -> &State {
+        self.state.as_ref().expect(LOST)
+    }
+
+    /// Returns the current state, consuming the driver.
+    pub fn into_state(self) -> State {
+        self.state.expect(LOST)
+    }
+
+    fn take(&mut self) -> State {
+        self.state.take().expect(LOST)
+    }
+}
+
+const LOST: &str = "the state is lost when a transition panics";
@@ -0,0 +1,631 @@
+use crate::{Closure, IndexedState};
+use core::convert::Infallible;
+use core::fmt;
+use core::marker::PhantomData;
+
+/// Allows the construction of computations that hold state and may fail.
+/// Given an input state of `I`, it either returns the value of `A` and the output state `O`,
+/// or an error in place of both.
+pub trait TryIndexedState<Input, Output, Value>: Sized {
+    /// The error returned when the computation fails.
+    type Error;
+
+    /// Given an input state, returns an output state as `O` and the inner value as `A`,
+    /// or the error when the computation fails.
+    /// All composable functions are derived from this function.
+    fn try_run(self, state: Input) -> Result<(Value, Output), Self::Error>;
+
+    /// Consumes the input state and returns only the inner value as `A`, or the error.
+    fn try_evaluate(self, state: Input) -> Result<Value, Self::Error> {
+        self.try_run(state).map(|(value, _)| value)
+    }
+
+    /// Consumes the input state and returns only the output state as `O`, or the error.
+    fn try_execute(self, state: Input) -> Result<Output, Self::Error> {
+        self.try_run(state).map(|(_, output)| output)
+    }
+
+    /// Applies a fallible covariant function to `A` that goes from `A` to `Result<B, E>`.
+    ///
+    /// When the function fails, the output state is dropped and the error is returned,
+    /// converting the error of this structure into `E` using `From`.
+    fn try_map<Covariant, SecondValue, Error>(
+        self,
+        covariant: Covariant,
+    ) -> TryMap<Self, Covariant, Value>
+    where
+        Covariant: FnOnce(Value) -> Result<SecondValue, Error>,
+        Error: From<Self::Error>,
+    {
+        TryMap {
+            first: self,
+            covariant,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Applies a fallible covariant function to the output state, that goes from `O` to `Result<P, E>`.
+    ///
+    /// When the function fails, the value is dropped and the error is returned,
+    /// converting the error of this structure into `E` using `From`.
+    fn try_map_state<Covariant, SecondOutput, Error>(
+        self,
+        covariant: Covariant,
+    ) -> TryMapState<Self, Covariant, Output>
+    where
+        Covariant: FnOnce(Output) -> Result<SecondOutput, Error>,
+        Error: From<Self::Error>,
+    {
+        TryMapState {
+            first: self,
+            covariant,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Applies a fallible contravariant function to the input state, that goes from `K` to `Result<I, E>`.
+    ///
+    /// When the function fails, this structure is never run and the error is returned,
+    /// converting the error of this structure into `E` using `From`.
+    /// The function owns the input state, so it can return it within `E` when needed.
+    fn try_contramap_state<Contravariant, FirstInput, Error>(
+        self,
+        contravariant: Contravariant,
+    ) -> TryContramapState<Self, Contravariant>
+    where
+        Contravariant: FnOnce(FirstInput) -> Result<Input, Error>,
+        Error: From<Self::Error>,
+    {
+        TryContramapState {
+            first: self,
+            contravariant,
+        }
+    }
+
+    /// Applies a function that goes from the error to an alternative fallible `Stateful` structure,
+    /// which is run when this structure fails.
+    ///
+    /// The input state is cloned before this structure runs, so the alternative is given
+    /// the original input state rather than any partially transformed state.
+    /// When the alternative also fails, its error is returned.
+    fn or_else<Fallback, Alternative>(self, fallback: Fallback) -> OrElse<Self, Fallback>
+    where
+        Input: Clone,
+        Fallback: FnOnce(Self::Error) -> Alternative,
+        Alternative: TryIndexedState<Input, Output, Value>,
+    {
+        OrElse {
+            first: self,
+            fallback,
+        }
+    }
+
+    /// Converts this structure into an infallible `IndexedState`, calling `recovery` with the error
+    /// and the original input state to create the value and output state when this structure fails.
+    ///
+    /// The input state is cloned before this structure runs, and `recovery` is not called on success.
+    fn recover<Recovery>(self, recovery: Recovery) -> Recover<Self, Recovery>
+    where
+        Input: Clone,
+        Recovery: FnOnce(Self::Error, Input) -> (Value, Output),
+    {
+        Recover {
+            first: self,
+            recovery,
+        }
+    }
+
+    /// Applies a covariant function to the error, leaving the value and output state untouched.
+    fn map_err<Covariant, Error>(self, covariant: Covariant) -> MapErr<Self, Covariant>
+    where
+        Covariant: FnOnce(Self::Error) -> Error,
+    {
+        MapErr {
+            first: self,
+            covariant,
+        }
+    }
+
+    /// Applies a function that goes from `A` to a new fallible `Stateful` structure,
+    /// which is run with the output state of this structure.
+    ///
+    /// When this structure fails, the function is never called and the error is returned,
+    /// converted into the error of the next structure using `From`.
+    fn try_and_then<Kleisli, Second, SecondOutput, SecondValue>(
+        self,
+        kleisli: Kleisli,
+    ) -> TryAndThen<Self, Kleisli, (Value, Output)>
+    where
+        Kleisli: FnOnce(Value) -> Second,
+        Second: TryIndexedState<Output, SecondOutput, SecondValue>,
+        Second::Error: From<Self::Error>,
+    {
+        TryAndThen {
+            stateful: self,
+            kleisli,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Input, Output, Value, Error, Function> TryIndexedState<Input, Output, Value> for Function
+where
+    Function: FnOnce(Input) -> Result<(Value, Output), Error>,
+{
+    type Error = Error;
+
+    fn try_run(self, state: Input) -> Result<(Value, Output), Error> {
+        self(state)
+    }
+}
+
+/// Lifts `value` into a fallible structure that returns the state untouched.
+pub fn ok<Value, Error>(value: Value) -> FromResult<Value, Error> {
+    from_result(Ok(value))
+}
+
+/// Creates a fallible structure that fails with `error` for any input state.
+///
+/// ```
+/// use indexed_state::{fail, ok, TryIndexedState};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct TooSmall;
+///
+/// let guard = |n: i32| ok::<_, TooSmall>(n).try_and_then(|v| if v > 3 { ok(v) } else { fail(TooSmall) });
+/// assert_eq!(guard(5).try_run("state"), Ok((5, "state")));
+/// assert_eq!(guard(2).try_run("state"), Err(TooSmall));
+/// ```
+pub fn fail<Value, Error>(error: Error) -> FromResult<Value, Error> {
+    from_result(Err(error))
+}
+
+/// Lifts `result` into a fallible structure that returns the state untouched when it is `Ok`,
+/// and fails with the error when it is `Err`.
+pub fn from_result<Value, Error>(result: Result<Value, Error>) -> FromResult<Value, Error> {
+    FromResult { result }
+}
+
+/// Runs the fallible structure made by `factory` up to `attempts` times, giving each attempt
+/// a clone of the original input state, until one of them succeeds.
+///
+/// When every attempt fails, the error of the last attempt is returned alongside the number of attempts made.
+/// At least one attempt is always made.
+pub fn retry<Input, Output, Value, Factory, First>(
+    attempts: usize,
+    factory: Factory,
+) -> Retry<Factory, fn(&First::Error, usize)>
+where
+    Input: Clone,
+    Factory: FnMut() -> First,
+    First: TryIndexedState<Input, Output, Value>,
+{
+    Retry {
+        attempts,
+        factory,
+        hook: |_, _| {},
+    }
+}
+
+/// A `Stateful` structure that never fails, created by `infallible`.
+#[derive(Clone, Copy)]
+pub struct Fallible<First> {
+    pub(crate) first: First,
+}
+
+impl<First> fmt::Debug for Fallible<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Fallible")
+            .field("first", &self.first)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First> TryIndexedState<Input, Output, Value> for Fallible<First>
+where
+    First: IndexedState<Input, Output, Value>,
+{
+    type Error = Infallible;
+
+    fn try_run(self, state: Input) -> Result<(Value, Output), Infallible> {
+        Ok(self.first.run(state))
+    }
+}
+
+pub struct TryMap<First, Covariant, Phantom> {
+    first: First,
+    covariant: Covariant,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Covariant, Phantom> fmt::Debug for TryMap<First, Covariant, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TryMap")
+            .field("first", &self.first)
+            .field("covariant", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Covariant, Phantom> Clone for TryMap<First, Covariant, Phantom>
+where
+    First: Clone,
+    Covariant: Clone,
+{
+    fn clone(&self) -> Self {
+        TryMap {
+            first: self.first.clone(),
+            covariant: self.covariant.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Covariant, Phantom> Copy for TryMap<First, Covariant, Phantom>
+where
+    First: Copy,
+    Covariant: Copy,
+{
+}
+
+impl<Input, Output, Value, SecondValue, Error, First, Covariant>
+    TryIndexedState<Input, Output, SecondValue> for TryMap<First, Covariant, Value>
+where
+    First: TryIndexedState<Input, Output, Value>,
+    Covariant: FnOnce(Value) -> Result<SecondValue, Error>,
+    Error: From<First::Error>,
+{
+    type Error = Error;
+
+    fn try_run(self, state: Input) -> Result<(SecondValue, Output), Error> {
+        let (a, o) = self.first.try_run(state)?;
+        let b = (self.covariant)(a)?;
+        Ok((b, o))
+    }
+}
+
+pub struct TryMapState<First, Covariant, Phantom> {
+    first: First,
+    covariant: Covariant,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Covariant, Phantom> fmt::Debug for TryMapState<First, Covariant, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TryMapState")
+            .field("first", &self.first)
+            .field("covariant", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Covariant, Phantom> Clone for TryMapState<First, Covariant, Phantom>
+where
+    First: Clone,
+    Covariant: Clone,
+{
+    fn clone(&self) -> Self {
+        TryMapState {
+            first: self.first.clone(),
+            covariant: self.covariant.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Covariant, Phantom> Copy for TryMapState<First, Covariant, Phantom>
+where
+    First: Copy,
+    Covariant: Copy,
+{
+}
+
+impl<Input, Output, SecondOutput, Value, Error, First, Covariant>
+    TryIndexedState<Input, SecondOutput, Value> for TryMapState<First, Covariant, Output>
+where
+    First: TryIndexedState<Input, Output, Value>,
+    Covariant: FnOnce(Output) -> Result<SecondOutput, Error>,
+    Error: From<First::Error>,
+{
+    type Error = Error;
+
+    fn try_run(self, state: Input) -> Result<(Value, SecondOutput), Error> {
+        let (a, o) = self.first.try_run(state)?;
+        let p = (self.covariant)(o)?;
+        Ok((a, p))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct TryContramapState<First, Contravariant> {
+    first: First,
+    contravariant: Contravariant,
+}
+
+impl<First, Contravariant> fmt::Debug for TryContramapState<First, Contravariant>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TryContramapState")
+            .field("first", &self.first)
+            .field("contravariant", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, PreviousInput, Error, First, Contravariant>
+    TryIndexedState<PreviousInput, Output, Value> for TryContramapState<First, Contravariant>
+where
+    First: TryIndexedState<Input, Output, Value>,
+    Contravariant: FnOnce(PreviousInput) -> Result<Input, Error>,
+    Error: From<First::Error>,
+{
+    type Error = Error;
+
+    fn try_run(self, state: PreviousInput) -> Result<(Value, Output), Error> {
+        let state = (self.contravariant)(state)?;
+        Ok(self.first.try_run(state)?)
+    }
+}
+
+pub struct TryAndThen<First, Kleisli, Phantom> {
+    stateful: First,
+    kleisli: Kleisli,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Kleisli, Phantom> fmt::Debug for TryAndThen<First, Kleisli, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TryAndThen")
+            .field("stateful", &self.stateful)
+            .field("kleisli", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Kleisli, Phantom> Clone for TryAndThen<First, Kleisli, Phantom>
+where
+    First: Clone,
+    Kleisli: Clone,
+{
+    fn clone(&self) -> Self {
+        TryAndThen {
+            stateful: self.stateful.clone(),
+            kleisli: self.kleisli.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Kleisli, Phantom> Copy for TryAndThen<First, Kleisli, Phantom>
+where
+    First: Copy,
+    Kleisli: Copy,
+{
+}
+
+impl<FirstInput, SecondInput, SecondOutput, FirstValue, SecondValue, First, Kleisli, Second>
+    TryIndexedState<FirstInput, SecondOutput, SecondValue>
+    for TryAndThen<First, Kleisli, (FirstValue, SecondInput)>
+where
+    First: TryIndexedState<FirstInput, SecondInput, FirstValue>,
+    Kleisli: FnOnce(FirstValue) -> Second,
+    Second: TryIndexedState<SecondInput, SecondOutput, SecondValue>,
+    Second::Error: From<First::Error>,
+{
+    type Error = Second::Error;
+
+    fn try_run(self, state: FirstInput) -> Result<(SecondValue, SecondOutput), Self::Error> {
+        let (a, o) = self.stateful.try_run(state)?;
+        (self.kleisli)(a).try_run(o)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct OrElse<First, Fallback> {
+    first: First,
+    fallback: Fallback,
+}
+
+impl<First, Fallback> fmt::Debug for OrElse<First, Fallback>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OrElse")
+            .field("first", &self.first)
+            .field("fallback", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First, Fallback, Alternative> TryIndexedState<Input, Output, Value>
+    for OrElse<First, Fallback>
+where
+    Input: Clone,
+    First: TryIndexedState<Input, Output, Value>,
+    Fallback: FnOnce(First::Error) -> Alternative,
+    Alternative: TryIndexedState<Input, Output, Value>,
+{
+    type Error = Alternative::Error;
+
+    fn try_run(self, state: Input) -> Result<(Value, Output), Self::Error> {
+        match self.first.try_run(state.clone()) {
+            Ok(result) => Ok(result),
+            Err(error) => (self.fallback)(error).try_run(state),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Recover<First, Recovery> {
+    first: First,
+    recovery: Recovery,
+}
+
+impl<First, Recovery> fmt::Debug for Recover<First, Recovery>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recover")
+            .field("first", &self.first)
+            .field("recovery", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First, Recovery> IndexedState<Input, Output, Value>
+    for Recover<First, Recovery>
+where
+    Input: Clone,
+    First: TryIndexedState<Input, Output, Value>,
+    Recovery: FnOnce(First::Error, Input) -> (Value, Output),
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        match self.first.try_run(state.clone()) {
+            Ok(result) => result,
+            Err(error) => (self.recovery)(error, state),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct MapErr<First, Covariant> {
+    first: First,
+    covariant: Covariant,
+}
+
+impl<First, Covariant> fmt::Debug for MapErr<First, Covariant>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapErr")
+            .field("first", &self.first)
+            .field("covariant", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, Error, First, Covariant> TryIndexedState<Input, Output, Value>
+    for MapErr<First, Covariant>
+where
+    First: TryIndexedState<Input, Output, Value>,
+    Covariant: FnOnce(First::Error) -> Error,
+{
+    type Error = Error;
+
+    fn try_run(self, state: Input) -> Result<(Value, Output), Error> {
+        self.first.try_run(state).map_err(self.covariant)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct FromResult<Value, Error> {
+    result: Result<Value, Error>,
+}
+
+impl<Value, Error> fmt::Debug for FromResult<Value, Error>
+where
+    Value: fmt::Debug,
+    Error: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FromResult")
+            .field("result", &self.result)
+            .finish()
+    }
+}
+
+impl<State, Value, Error> TryIndexedState<State, State, Value> for FromResult<Value, Error> {
+    type Error = Error;
+
+    fn try_run(self, state: State) -> Result<(Value, State), Error> {
+        self.result.map(|value| (value, state))
+    }
+}
+
+/// The error of the last attempt made by `retry`, along with the number of attempts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryError<Error> {
+    pub attempts: usize,
+    pub error: Error,
+}
+
+impl<Error> fmt::Display for RetryError<Error>
+where
+    Error: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed after {} attempts: {}", self.attempts, self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Error> std::error::Error for RetryError<Error> where Error: std::error::Error {}
+
+#[derive(Clone, Copy)]
+pub struct Retry<Factory, Hook> {
+    attempts: usize,
+    factory: Factory,
+    hook: Hook,
+}
+
+impl<Factory, Hook> fmt::Debug for Retry<Factory, Hook> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Retry")
+            .field("attempts", &self.attempts)
+            .field("factory", &Closure)
+            .field("hook", &Closure)
+            .finish()
+    }
+}
+
+impl<Factory, Hook> Retry<Factory, Hook> {
+    /// Calls `hook` with the error and number of the attempt that failed, before the next attempt is made.
+    pub fn on_retry<SecondHook>(self, hook: SecondHook) -> Retry<Factory, SecondHook> {
+        Retry {
+            attempts: self.attempts,
+            factory: self.factory,
+            hook,
+        }
+    }
+}
+
+impl<Input, Output, Value, Factory, Hook, First> TryIndexedState<Input, Output, Value>
+    for Retry<Factory, Hook>
+where
+    Input: Clone,
+    Factory: FnMut() -> First,
+    Hook: FnMut(&First::Error, usize),
+    First: TryIndexedState<Input, Output, Value>,
+{
+    type Error = RetryError<First::Error>;
+
+    fn try_run(mut self, state: Input) -> Result<(Value, Output), Self::Error> {
+        let attempts = self.attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            match (self.factory)().try_run(state.clone()) {
+                Ok(result) => return Ok(result),
+                Err(error) if attempt == attempts => return Err(RetryError { attempts, error }),
+                Err(error) => (self.hook)(&error, attempt),
+            }
+            attempt += 1;
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::IndexedState;
+use core::fmt;
+use std::time::Instant;
+
+/// Combinators that instrument `Stateful` structures with `tracing` spans.
+pub trait Instrument<Input, Output, Value>: IndexedState<Input, Output, Value> {
+    /// Runs this structure inside an `indexed_state` span with `name` as a field,
+    /// emitting an event with the elapsed time once it has run.
+    fn instrumented(self, name: &'static str) -> Instrumented<Self> {
+        Instrumented { first: self, name }
+    }
+
+    /// Like `instrumented`, also recording the output state `O` as the `state` field of the span.
+    fn instrumented_with_state(self, name: &'static str) -> InstrumentedWithState<Self>
+    where
+        Output: fmt::Debug,
+    {
+        InstrumentedWithState { first: self, name }
+    }
+}
+
+impl<Input, Output, Value, First> Instrument<Input, Output, Value> for First where
+    First: IndexedState<Input, Output, Value>
+{
+}
+
+#[derive(Clone, Copy)]
+pub struct Instrumented<First> {
+    first: First,
+    name: &'static str,
+}
+
+impl<First> fmt::Debug for Instrumented<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Instrumented")
+            .field("first", &self.first)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First> IndexedState<Input, Output, Value> for Instrumented<First>
+where
+    First: IndexedState<Input, Output, Value>,
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        let span = tracing::info_span!("indexed_state", name = self.name);
+        let _entered = span.enter();
+        let start = Instant::now();
+        let (a, o) = self.first.run(state);
+        tracing::trace!(elapsed = ?start.elapsed(), "ran");
+        (a, o)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct InstrumentedWithState<First> {
+    first: First,
+    name: &'static str,
+}
+
+impl<First> fmt::Debug for InstrumentedWithState<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InstrumentedWithState")
+            .field("first", &self.first)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First> IndexedState<Input, Output, Value>
+    for InstrumentedWithState<First>
+where
+    First: IndexedState<Input, Output, Value>,
+    Output: fmt::Debug,
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        let span = tracing::info_span!(
+            "indexed_state",
+            name = self.name,
+            state = tracing::field::Empty
+        );
+        let _entered = span.enter();
+        let start = Instant::now();
+        let (a, o) = self.first.run(state);
+        span.record("state", tracing::field::debug(&o));
+        tracing::trace!(elapsed = ?start.elapsed(), "ran");
+        (a, o)
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{Closure, IndexedState};
+use core::fmt;
+use core::marker::PhantomData;
+
+/// Lazily runs `step` over and over, starting from `init` and threading the state through each run,
+/// yielding the output state of every run.
+///
+/// The step is cloned for every run, and the state is cloned once per item yielded.
+/// The values of `A` are discarded, so they do not need to be `Clone`.
+pub fn iterate<State, Value, Step>(step: Step, init: State) -> Iterate<Step, State, Value>
+where
+    State: Clone,
+    Step: IndexedState<State, State, Value> + Clone,
+{
+    Iterate {
+        step,
+        state: Some(init),
+        phantom: PhantomData,
+    }
+}
+
+pub struct Iterate<Step, State, Phantom> {
+    step: Step,
+    state: Option<State>,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<Step, State, Phantom> fmt::Debug for Iterate<Step, State, Phantom>
+where
+    Step: fmt::Debug,
+    State: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Iterate")
+            .field("step", &self.step)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<Step, State, Phantom> Clone for Iterate<Step, State, Phantom>
+where
+    Step: Clone,
+    State: Clone,
+{
+    fn clone(&self) -> Self {
+        Iterate {
+            step: self.step.clone(),
+            state: self.state.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<State, Value, Step> Iterator for Iterate<Step, State, Value>
+where
+    State: Clone,
+    Step: IndexedState<State, State, Value> + Clone,
+{
+    type Item = State;
+
+    fn next(&mut self) -> Option<State> {
+        let state = self.step.clone().execute(self.state.take()?);
+        self.state = Some(state.clone());
+        Some(state)
+    }
+}
+
+/// Lazily runs `step` over and over, starting from `init` and threading the state through each run,
+/// yielding the value of `A` of every run while the state stays inside the iterator.
+///
+/// The step is cloned for every run, and the state can be recovered with `into_state`.
+pub fn values<State, Value, Step>(step: Step, init: State) -> ValuesIter<Step, State, Value>
+where
+    Step: IndexedState<State, State, Value> + Clone,
+{
+    ValuesIter {
+        step,
+        state: Some(init),
+        phantom: PhantomData,
+    }
+}
+
+pub struct ValuesIter<Step, State, Phantom> {
+    step: Step,
+    state: Option<State>,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<Step, State, Phantom> fmt::Debug for ValuesIter<Step, State, Phantom>
+where
+    Step: fmt::Debug,
+    State: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValuesIter")
+            .field("step", &self.step)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<Step, State, Phantom> Clone for ValuesIter<Step, State, Phantom>
+where
+    Step: Clone,
+    State: Clone,
+{
+    fn clone(&self) -> Self {
+        ValuesIter {
+            step: self.step.clone(),
+            state: self.state.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Step, State, Phantom> ValuesIter<Step, State, Phantom> {
+    /// Returns the state after the last value that was yielded.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the step panicked while running, as the state is lost.
+    pub fn into_state(self) -> State {
+        self.state.expect("the state is lost when the step panics")
+    }
+}
+
+impl<State, Value, Step> Iterator for ValuesIter<Step, State, Value>
+where
+    Step: IndexedState<State, State, Value> + Clone,
+{
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        let (a, o) = self.step.clone().run(self.state.take()?);
+        self.state = Some(o);
+        Some(a)
+    }
+}
+
+/// Combinators that thread a state through the items of an iterator.
+pub trait IteratorStateExt: Iterator + Sized {
+    /// Lazily applies the structure created by `kleisli` to every item, starting from `init` and
+    /// threading the state through each of them, yielding the value of `A` for every item.
+    ///
+    /// The state can be borrowed with `state` or recovered with `finish`.
+    fn scan_state<State, Value, Kleisli, Second>(
+        self,
+        init: State,
+        kleisli: Kleisli,
+    ) -> ScanState<Self, Kleisli, State, Value>
+    where
+        Kleisli: FnMut(Self::Item) -> Second,
+        Second: IndexedState<State, State, Value>,
+    {
+        ScanState {
+            items: self,
+            kleisli,
+            state: Some(init),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Eagerly applies the structure created by `kleisli` to every item once collected with
+    /// `collect_with_state`, starting from `init` and threading the state through each of them.
+    fn thread<State, Value, Kleisli, Second>(
+        self,
+        init: State,
+        kleisli: Kleisli,
+    ) -> Thread<Self, Kleisli, State, Value>
+    where
+        Kleisli: FnMut(Self::Item) -> Second,
+        Second: IndexedState<State, State, Value>,
+    {
+        Thread {
+            scan: self.scan_state(init, kleisli),
+        }
+    }
+}
+
+impl<Items> IteratorStateExt for Items where Items: Iterator {}
+
+pub struct ScanState<Items, Kleisli, State, Phantom> {
+    items: Items,
+    kleisli: Kleisli,
+    state: Option<State>,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<Items, Kleisli, State, Phantom> fmt::Debug for ScanState<Items, Kleisli, State, Phantom>
+where
+    Items: fmt::Debug,
+    State: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScanState")
+            .field("items", &self.items)
+            .field("kleisli", &Closure)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<Items, Kleisli, State, Phantom> Clone for ScanState<Items, Kleisli, State, Phantom>
+where
+    Items: Clone,
+    Kleisli: Clone,
+    State: Clone,
+{
+    fn clone(&self) -> Self {
+        ScanState {
+            items: self.items.clone(),
+            kleisli: self.kleisli.clone(),
+            state: self.state.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Items, Kleisli, State, Phantom> ScanState<Items, Kleisli, State, Phantom> {
+    /// Borrows the state after the last value that was yielded.
+    ///
+    /// # Panics
+    ///
+    /// Panics when a structure panicked while running, as the state is lost.
+    pub fn state(&self) -> &State {
+        self.state
+            .as_ref()
+            .expect("the state is lost when a structure panics")
+    }
+
+    /// Returns the state after the last value that was yielded.
+    ///
+    /// # Panics
+    ///
+    /// Panics when a structure panicked while running, as the state is lost.
+    pub fn finish(self) -> State {
+        self.state
+            .expect("the state is lost when a structure panics")
+    }
+}
+
+impl<State, Value, Items, Kleisli, Second> Iterator for ScanState<Items, Kleisli, State, Value>
+where
+    Items: Iterator,
+    Kleisli: FnMut(Items::Item) -> Second,
+    Second: IndexedState<State, State, Value>,
+{
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        let item = self.items.next()?;
+        let (a, o) = (self.kleisli)(item).run(self.state.take()?);
+        self.state = Some(o);
+        Some(a)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.items.size_hint()
+    }
+}
+
+pub struct Thread<Items, Kleisli, State, Phantom> {
+    scan: ScanState<Items, Kleisli, State, Phantom>,
+}
+
+impl<Items, Kleisli, State, Phantom> fmt::Debug for Thread<Items, Kleisli, State, Phantom>
+where
+    Items: fmt::Debug,
+    State: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Thread").field("scan", &self.scan).finish()
+    }
+}
+
+impl<Items, Kleisli, State, Phantom> Clone for Thread<Items, Kleisli, State, Phantom>
+where
+    Items: Clone,
+    Kleisli: Clone,
+    State: Clone,
+{
+    fn clone(&self) -> Self {
+        Thread {
+            scan: self.scan.clone(),
+        }
+    }
+}
+
+impl<State, Value, Items, Kleisli, Second> Thread<Items, Kleisli, State, Value>
+where
+    Items: Iterator,
+    Kleisli: FnMut(Items::Item) -> Second,
+    Second: IndexedState<State, State, Value>,
+{
+    /// Collects the value of `A` for every item into `Collection` in a single pass,
+    /// returning it alongside the final state.
+    pub fn collect_with_state<Collection>(mut self) -> (Collection, State)
+    where
+        Collection: FromIterator<Value>,
+    {
+        let collection = self.scan.by_ref().collect();
+        (collection, self.scan.finish())
+    }
+}
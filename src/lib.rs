@@ -1,4 +1,76 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use core::fmt;
 use core::marker::PhantomData;
+use core::ops::ControlFlow;
+use core::panic::Location;
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::panic::UnwindSafe;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+#[cfg(feature = "alloc")]
+mod boxed;
+#[cfg(feature = "anyhow")]
+mod context;
+mod driver;
+mod fallible;
+#[cfg(feature = "tracing")]
+mod instrument;
+mod iterate;
+#[cfg(feature = "std")]
+mod memoize;
+#[cfg(feature = "std")]
+mod observe;
+mod option;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod pipeline;
+#[cfg(feature = "alloc")]
+mod repeat;
+mod result;
+mod reusable;
+#[cfg(feature = "alloc")]
+mod shared;
+#[cfg(feature = "alloc")]
+mod traverse;
+#[cfg(feature = "std")]
+mod unwind;
+
+#[cfg(feature = "alloc")]
+pub use boxed::*;
+#[cfg(feature = "anyhow")]
+pub use context::*;
+pub use driver::*;
+pub use fallible::*;
+#[cfg(feature = "tracing")]
+pub use instrument::*;
+pub use iterate::*;
+#[cfg(feature = "std")]
+pub use memoize::*;
+#[cfg(feature = "std")]
+pub use observe::*;
+pub use option::*;
+#[cfg(feature = "rayon")]
+pub use parallel::*;
+pub use pipeline::*;
+#[cfg(feature = "alloc")]
+pub use repeat::*;
+pub use result::*;
+pub use reusable::*;
+#[cfg(feature = "alloc")]
+pub use shared::*;
+#[cfg(feature = "alloc")]
+pub use traverse::*;
+#[cfg(feature = "std")]
+pub use unwind::*;
 
 /// Allows the construction of computations that hold state; the indexed state monad pattern.
 /// Given an input state of `I`, it can return the value of `A` whilst keeping any changes to the output state as `O`.
@@ -19,6 +91,48 @@ pub trait IndexedState<Input, Output, Value>: Sized {
         self.run(state).1
     }
 
+    /// Like `run`, using the default value of the input state.
+    fn run_default(self) -> (Value, Output)
+    where
+        Input: Default,
+    {
+        self.run(Input::default())
+    }
+
+    /// Like `evaluate`, using the default value of the input state.
+    fn evaluate_default(self) -> Value
+    where
+        Input: Default,
+    {
+        self.evaluate(Input::default())
+    }
+
+    /// Like `execute`, using the default value of the input state.
+    fn execute_default(self) -> Output
+    where
+        Input: Default,
+    {
+        self.execute(Input::default())
+    }
+
+    /// Like `run`, cloning this structure first so it can be run again afterwards.
+    /// The structure is cloned once per call.
+    fn run_cloned(&self, state: Input) -> (Value, Output)
+    where
+        Self: Clone,
+    {
+        self.clone().run(state)
+    }
+
+    /// Like `run`, cloning the borrowed input state so the caller keeps ownership of it.
+    /// The input state is cloned once per call.
+    fn run_with_ref(self, state: &Input) -> (Value, Output)
+    where
+        Input: Clone,
+    {
+        self.run(state.clone())
+    }
+
     /// Applies a covariant function to `A` that goes from `A` to `B`.
     fn map<Function, ValueNext>(self, closure: Function) -> Map<Self, Function, Value>
     where
@@ -82,153 +196,3706 @@ pub trait IndexedState<Input, Output, Value>: Sized {
     }
 
     /// Applies a function to a value that are both wrapped in `Stateful`.
-    fn apply<Second, SecondInput, Covariant>(
+    /// This structure holds the function and runs first, then `second` holds the value.
+    fn apply<Second, SecondOutput, SecondValue, ThirdValue>(
         self,
         second: Second,
-    ) -> Apply<Self, Second, (SecondInput, Value, Covariant)> {
+    ) -> Apply<Self, Second, (Output, SecondValue, Value)>
+    where
+        Second: IndexedState<Output, SecondOutput, SecondValue>,
+        Value: FnOnce(SecondValue) -> ThirdValue,
+    {
         Apply {
             first: self,
             second,
             phantom: PhantomData,
         }
     }
-}
 
-impl<FirstInput, SecondInput, FirstValue, Covariant>
-    IndexedState<FirstInput, SecondInput, FirstValue> for Covariant
-where
-    Covariant: FnOnce(FirstInput) -> (FirstValue, SecondInput),
-{
-    fn run(self, state: FirstInput) -> (FirstValue, SecondInput) {
-        self(state)
+    /// Discards the value of `A` and replaces it with `value`, keeping the state transition intact.
+    fn replace<NewValue>(self, value: NewValue) -> Replace<Self, NewValue, Value> {
+        Replace {
+            first: self,
+            value,
+            phantom: PhantomData,
+        }
     }
-}
 
-/// Create a `Stateful` structure given the input type can be cloned.
-pub fn new<State>() -> impl IndexedState<State, State, State>
-where
-    State: Clone,
-{
-    |state: State| (state.clone(), state)
-}
+    /// Discards the value of `A`, returning `()` whilst keeping the state transition intact.
+    fn void(self) -> Void<Self, Value> {
+        Void {
+            first: self,
+            phantom: PhantomData,
+        }
+    }
 
-pub fn gets<Input, Output>(
-    covariant: impl FnOnce(Input) -> Output,
-) -> impl IndexedState<Input, Output, Output>
-where
-    Output: Clone,
-{
-    |state: Input| {
-        let output = covariant(state);
-        (output.clone(), output)
+    /// Runs `second` with the output state of this structure, returning both values as a tuple.
+    fn zip<Second, SecondOutput, SecondValue>(
+        self,
+        second: Second,
+    ) -> Zip<Self, Second, (Value, Output)>
+    where
+        Second: IndexedState<Output, SecondOutput, SecondValue>,
+    {
+        Zip {
+            first: self,
+            second,
+            phantom: PhantomData,
+        }
     }
-}
 
-pub fn gots<Input, Value>(
-    covariant: impl FnOnce(Input) -> Value,
-) -> impl IndexedState<Input, Input, Value>
-where
-    Input: Clone,
-{
-    |state: Input| {
-        let value = covariant(state.clone());
-        (value, state)
+    /// Runs `second` with the output state of this structure,
+    /// combining both values with a function that goes from `A` and `B` to `C`.
+    fn zip_with<Second, SecondOutput, SecondValue, Covariant, ThirdValue>(
+        self,
+        second: Second,
+        covariant: Covariant,
+    ) -> ZipWith<Self, Second, Covariant, (Value, Output, SecondValue)>
+    where
+        Second: IndexedState<Output, SecondOutput, SecondValue>,
+        Covariant: FnOnce(Value, SecondValue) -> ThirdValue,
+    {
+        ZipWith {
+            first: self,
+            second,
+            covariant,
+            phantom: PhantomData,
+        }
     }
-}
 
-pub struct Map<First, Covariant, Phantom> {
-    first: First,
-    covariant: Covariant,
-    phantom: PhantomData<Phantom>,
-}
+    /// Runs `second` and then `third`, threading the state through all three structures
+    /// and returning the values as a flat tuple.
+    ///
+    /// For higher arities, see the `zip!` macro.
+    fn zip3<Second, SecondOutput, SecondValue, Third, ThirdOutput, ThirdValue>(
+        self,
+        second: Second,
+        third: Third,
+    ) -> Zip3<Self, Second, Third, (Value, Output, SecondValue, SecondOutput)>
+    where
+        Second: IndexedState<Output, SecondOutput, SecondValue>,
+        Third: IndexedState<SecondOutput, ThirdOutput, ThirdValue>,
+    {
+        Zip3 {
+            first: self,
+            second,
+            third,
+            phantom: PhantomData,
+        }
+    }
 
-impl<FirstInput, SecondInput, SecondValue, First, Covariant, FirstValue>
-    IndexedState<FirstInput, SecondInput, SecondValue> for Map<First, Covariant, FirstValue>
-where
-    First: IndexedState<FirstInput, SecondInput, FirstValue>,
-    Covariant: FnOnce(FirstValue) -> SecondValue,
-{
-    fn run(self, state: FirstInput) -> (SecondValue, SecondInput) {
-        let (a, o) = self.first.run(state);
-        let b = (self.covariant)(a);
-        (b, o)
+    /// Discards the value of `A` and runs `second` with the output state of this structure.
+    fn then<Second, SecondOutput, SecondValue>(
+        self,
+        second: Second,
+    ) -> Then<Self, Second, (Value, Output)>
+    where
+        Second: IndexedState<Output, SecondOutput, SecondValue>,
+    {
+        Then {
+            first: self,
+            second,
+            phantom: PhantomData,
+        }
     }
-}
 
-pub struct MapState<First, Covariant, Phantom> {
-    first: First,
-    covariant: Covariant,
-    phantom: PhantomData<Phantom>,
-}
+    /// Runs this structure and then `second`, returning only the value of this structure.
+    /// The state reflects both structures, as `second` runs with the output state of this one.
+    fn zip_left<Second, SecondOutput, SecondValue>(
+        self,
+        second: Second,
+    ) -> ZipLeft<Self, Second, (Output, SecondValue)>
+    where
+        Second: IndexedState<Output, SecondOutput, SecondValue>,
+    {
+        ZipLeft {
+            first: self,
+            second,
+            phantom: PhantomData,
+        }
+    }
 
-impl<FirstInput, SecondInput, SecondOutput, First, Function, FirstValue>
-    IndexedState<FirstInput, SecondOutput, FirstValue> for MapState<First, Function, SecondInput>
-where
-    First: IndexedState<FirstInput, SecondInput, FirstValue>,
-    Function: FnOnce(SecondInput) -> SecondOutput,
-{
-    fn run(self, state: FirstInput) -> (FirstValue, SecondOutput) {
-        let (a, o) = self.first.run(state);
-        let p = (self.covariant)(o);
-        (a, p)
+    /// Runs this structure and then `second`, returning only the value of `second`.
+    /// The state reflects both structures, as `second` runs with the output state of this one.
+    fn zip_right<Second, SecondOutput, SecondValue>(
+        self,
+        second: Second,
+    ) -> ZipRight<Self, Second, (Value, Output)>
+    where
+        Second: IndexedState<Output, SecondOutput, SecondValue>,
+    {
+        ZipRight {
+            first: self,
+            second,
+            phantom: PhantomData,
+        }
     }
-}
 
-pub struct AndThen<First, Kleisli, Phantom> {
-    stateful: First,
-    kleisli: Kleisli,
-    phantom: PhantomData<Phantom>,
-}
+    /// Runs the structure returned as the value of `A` with the output state of this structure.
+    /// This is equivilent to `and_then` with the identity function.
+    fn flatten<SecondOutput, SecondValue>(self) -> Flatten<Self, (Value, Output)>
+    where
+        Value: IndexedState<Output, SecondOutput, SecondValue>,
+    {
+        Flatten {
+            first: self,
+            phantom: PhantomData,
+        }
+    }
 
-impl<FirstInput, SecondOutput, FirstValue, First, Kleisli, SecondInput, Second, SecondValue>
-    IndexedState<FirstInput, SecondOutput, SecondValue>
-    for AndThen<First, Kleisli, (FirstValue, SecondInput)>
-where
-    First: IndexedState<FirstInput, SecondInput, FirstValue>,
-    Second: IndexedState<SecondInput, SecondOutput, SecondValue>,
-    Kleisli: FnOnce(FirstValue) -> Second,
-{
-    fn run(self, state: FirstInput) -> (SecondValue, SecondOutput) {
-        let (a, o) = self.stateful.run(state);
-        (self.kleisli)(a).run(o)
+    /// Like `and_then`, but the function also borrows the output state of this structure,
+    /// so the next structure can be chosen based on both the value and the state.
+    /// The borrowed state is then used as the input state for the next structure.
+    fn and_then_with_state<Kleisli, Second, SecondOutput, SecondValue>(
+        self,
+        kleisli: Kleisli,
+    ) -> AndThenWithState<Self, Kleisli, (Value, Output)>
+    where
+        Second: IndexedState<Output, SecondOutput, SecondValue>,
+        Kleisli: FnOnce(Value, &Output) -> Second,
+    {
+        AndThenWithState {
+            stateful: self,
+            kleisli,
+            phantom: PhantomData,
+        }
     }
-}
 
-pub struct ContramapState<First, Contravariant> {
-    first: First,
-    contravariant: Contravariant,
-}
+    /// Applies a function that borrows the output state `O` and returns a new `Stateful` structure,
+    /// which is then run with that state to create the output state `P`.
+    ///
+    /// The value of the new structure is discarded, keeping the value of `A` from this one.
+    fn flat_map_state<Kleisli, Second, SecondOutput, SecondValue>(
+        self,
+        kleisli: Kleisli,
+    ) -> FlatMapState<Self, Kleisli, (Output, SecondValue)>
+    where
+        Second: IndexedState<Output, SecondOutput, SecondValue>,
+        Kleisli: FnOnce(&Output) -> Second,
+    {
+        FlatMapState {
+            stateful: self,
+            kleisli,
+            phantom: PhantomData,
+        }
+    }
 
-impl<Input, Output, Value, PreviousInput, Second, Contravariant>
-    IndexedState<PreviousInput, Output, Value> for ContramapState<Second, Contravariant>
-where
-    Second: IndexedState<Input, Output, Value>,
-    Contravariant: FnOnce(PreviousInput) -> Input,
-{
-    fn run(self, state: PreviousInput) -> (Value, Output) {
-        let state = (self.contravariant)(state);
-        self.first.run(state)
+    /// Calls a function with a reference to the value of `A`, returning the value and state unchanged.
+    fn inspect<Function>(self, function: Function) -> Inspect<Self, Function>
+    where
+        Function: FnOnce(&Value),
+    {
+        Inspect {
+            first: self,
+            function,
+        }
     }
-}
 
-pub struct Apply<First, Second, Phantom> {
-    first: First,
-    second: Second,
-    phantom: PhantomData<Phantom>,
-}
+    /// Calls a function with a reference to the output state `O`, returning the value and state unchanged.
+    fn inspect_state<Function>(self, function: Function) -> InspectState<Self, Function>
+    where
+        Function: FnOnce(&Output),
+    {
+        InspectState {
+            first: self,
+            function,
+        }
+    }
 
-impl<FirstInput, SecondInput, SecondOutput, FirstValue, Covariant, First, Second, SecondValue>
-    IndexedState<FirstInput, SecondOutput, SecondValue>
-    for Apply<First, Second, (SecondInput, FirstValue, Covariant)>
-where
-    First: IndexedState<FirstInput, SecondInput, Covariant>,
-    Second: IndexedState<SecondInput, SecondOutput, FirstValue>,
-    Covariant: FnOnce(FirstValue) -> SecondValue,
-{
-    fn run(self, state: FirstInput) -> (SecondValue, SecondOutput) {
-        let (f, state) = self.first.run(state);
-        let (a, p) = self.second.run(state);
-        let b = f(a);
-        (b, p)
+    /// Calls a function with a mutable reference to the value of `A`, leaving the state untouched.
+    fn tap_mut<Function>(self, function: Function) -> TapMut<Self, Function>
+    where
+        Function: FnOnce(&mut Value),
+    {
+        TapMut {
+            first: self,
+            function,
+        }
     }
-}
+
+    /// Applies covariant functions to both `A` and `O` in a single step,
+    /// where each function receives the value or state exactly as returned by `run`.
+    fn bimap<Covariant, SecondValue, CovariantState, SecondOutput>(
+        self,
+        covariant: Covariant,
+        covariant_state: CovariantState,
+    ) -> Bimap<Self, Covariant, CovariantState, (Value, Output)>
+    where
+        Covariant: FnOnce(Value) -> SecondValue,
+        CovariantState: FnOnce(Output) -> SecondOutput,
+    {
+        Bimap {
+            first: self,
+            covariant,
+            covariant_state,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Applies a covariant function to `A` that also borrows the output state `O`,
+    /// returning the state unmodified.
+    fn map_with_state<Covariant, SecondValue>(
+        self,
+        covariant: Covariant,
+    ) -> MapWithState<Self, Covariant, Value>
+    where
+        Covariant: FnOnce(Value, &Output) -> SecondValue,
+    {
+        MapWithState {
+            first: self,
+            covariant,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Applies a covariant function to the output state `O` that also borrows the value of `A`,
+    /// returning the value unmodified.
+    fn map_state_with_value<Covariant, SecondOutput>(
+        self,
+        covariant: Covariant,
+    ) -> MapStateWithValue<Self, Covariant, Output>
+    where
+        Covariant: FnOnce(Output, &Value) -> SecondOutput,
+    {
+        MapStateWithValue {
+            first: self,
+            covariant,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Pairs the value of `A` with a clone of the output state `O`, which is also still returned as the state.
+    fn with_state(self) -> WithState<Self>
+    where
+        Output: Clone,
+    {
+        WithState { first: self }
+    }
+
+    /// Pairs the value of `A` with a clone of the input state `I`.
+    /// The clone is taken before this structure runs, so it is the state as it was provided.
+    fn with_input(self) -> WithInput<Self>
+    where
+        Input: Clone,
+    {
+        WithInput { first: self }
+    }
+
+    /// Exchanges the value of `A` and the output state `O`,
+    /// so the value becomes the output state and the output state becomes the value.
+    fn swap_channels(self) -> SwapChannels<Self> {
+        SwapChannels { first: self }
+    }
+
+    /// Converts the value of `A` into `B` using `Into`.
+    fn map_into<SecondValue>(self) -> MapInto<Self, (Value, SecondValue)>
+    where
+        Value: Into<SecondValue>,
+    {
+        MapInto {
+            first: self,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Converts the output state `O` into `P` using `Into`.
+    fn state_into<SecondOutput>(self) -> StateInto<Self, (Output, SecondOutput)>
+    where
+        Output: Into<SecondOutput>,
+    {
+        StateInto {
+            first: self,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Applies a function wrapped in `functions` to the value of this structure.
+    ///
+    /// Unlike `apply`, this structure runs first and `functions` runs second,
+    /// so the function can be chosen based on the output state of this structure.
+    fn ap_flipped<Functions, SecondOutput, Covariant, SecondValue>(
+        self,
+        functions: Functions,
+    ) -> ApFlipped<Self, Functions, (Value, Output, Covariant)>
+    where
+        Functions: IndexedState<Output, SecondOutput, Covariant>,
+        Covariant: FnOnce(Value) -> SecondValue,
+    {
+        ApFlipped {
+            first: self,
+            functions,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Binds the input state `I` now, returning a structure that runs later with `()` as the input state.
+    fn provide(self, state: Input) -> Provided<Self, Input> {
+        Provided { first: self, state }
+    }
+
+    /// Applies a covariant function to the first element of a tuple shaped value,
+    /// moving the second element through untouched.
+    fn map_first<First, Second, Covariant, FirstNext>(
+        self,
+        covariant: Covariant,
+    ) -> MapFirst<Self, Covariant, First>
+    where
+        Self: IndexedState<Input, Output, (First, Second)>,
+        Covariant: FnOnce(First) -> FirstNext,
+    {
+        MapFirst {
+            first: self,
+            covariant,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Applies a covariant function to the second element of a tuple shaped value,
+    /// moving the first element through untouched.
+    fn map_second<First, Second, Covariant, SecondNext>(
+        self,
+        covariant: Covariant,
+    ) -> MapSecond<Self, Covariant, Second>
+    where
+        Self: IndexedState<Input, Output, (First, Second)>,
+        Covariant: FnOnce(Second) -> SecondNext,
+    {
+        MapSecond {
+            first: self,
+            covariant,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns `Some` with the value of `A` if the predicate holds, otherwise `None`.
+    /// The state transition is always applied.
+    fn filter<Predicate>(self, predicate: Predicate) -> Filter<Self, Predicate>
+    where
+        Predicate: FnOnce(&Value) -> bool,
+    {
+        Filter {
+            first: self,
+            predicate,
+        }
+    }
+
+    /// Continues with the structure returned by `on_true` or `on_false`, depending on a boolean value.
+    /// Only the function for the matching branch is called, and both structures must have the
+    /// same state and value types.
+    fn if_else<OnTrue, OnFalse, Second, Third, SecondOutput, SecondValue>(
+        self,
+        on_true: OnTrue,
+        on_false: OnFalse,
+    ) -> IfElse<Self, OnTrue, OnFalse, Output>
+    where
+        Self: IndexedState<Input, Output, bool>,
+        OnTrue: FnOnce() -> Second,
+        OnFalse: FnOnce() -> Third,
+        Second: IndexedState<Output, SecondOutput, SecondValue>,
+        Third: IndexedState<Output, SecondOutput, SecondValue>,
+    {
+        IfElse {
+            first: self,
+            on_true,
+            on_false,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns `Ok` with the value of `A` if the predicate holds,
+    /// otherwise `Err` with the error created from a reference to the value.
+    /// The state transition is always applied, whether the predicate holds or not.
+    fn ensure<Predicate, Error, FirstError>(
+        self,
+        predicate: Predicate,
+        error: Error,
+    ) -> Ensure<Self, Predicate, Error>
+    where
+        Predicate: FnOnce(&Value) -> bool,
+        Error: FnOnce(&Value) -> FirstError,
+    {
+        Ensure {
+            first: self,
+            predicate,
+            error,
+        }
+    }
+
+    /// Returns `Ok` with the value of `A` if the predicate holds for the output state,
+    /// otherwise `Err` with the error created from a reference to the state.
+    /// The output state is returned either way, so an invalid state can still be inspected.
+    fn ensure_state<Predicate, Error, FirstError>(
+        self,
+        predicate: Predicate,
+        error: Error,
+    ) -> EnsureState<Self, Predicate, Error>
+    where
+        Predicate: FnOnce(&Output) -> bool,
+        Error: FnOnce(&Output) -> FirstError,
+    {
+        EnsureState {
+            first: self,
+            predicate,
+            error,
+        }
+    }
+
+    /// Runs this structure for its value only, returning a clone of the input state `I`
+    /// as the output state and discarding the output state `O`.
+    fn reset(self) -> Reset<Self, Output>
+    where
+        Input: Clone,
+    {
+        Reset {
+            first: self,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Runs this structure against the state created by `enter` from a reference to the input state,
+    /// then returns the untouched input state as the output state.
+    ///
+    /// This structure observes the state created by `enter`, but its changes are not kept.
+    /// As the input state is only borrowed, it does not need to be cloned.
+    fn local<Enter>(self, enter: Enter) -> Local<Self, Enter, Output>
+    where
+        Enter: FnOnce(&Input) -> Input,
+    {
+        Local {
+            first: self,
+            enter,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Writes the label, the value of `A` and the output state `O` to `writer` using `Debug`,
+    /// along with the location this was called from, returning everything unchanged.
+    #[track_caller]
+    fn dbg_to<Writer>(self, writer: Writer, label: &'static str) -> Dbg<Self, Writer>
+    where
+        Writer: fmt::Write,
+        Value: fmt::Debug,
+        Output: fmt::Debug,
+    {
+        Dbg {
+            first: self,
+            writer,
+            label,
+            location: Location::caller(),
+        }
+    }
+
+    /// Like `dbg_to`, writing only the output state `O` so the value does not need to be `Debug`.
+    #[track_caller]
+    fn dbg_state_to<Writer>(self, writer: Writer, label: &'static str) -> DbgState<Self, Writer>
+    where
+        Writer: fmt::Write,
+        Output: fmt::Debug,
+    {
+        DbgState {
+            first: self,
+            writer,
+            label,
+            location: Location::caller(),
+        }
+    }
+
+    /// Prints the label, the value of `A` and the output state `O` to standard error like `dbg!`,
+    /// returning everything unchanged.
+    #[cfg(feature = "std")]
+    #[track_caller]
+    fn dbg(self, label: &'static str) -> Dbg<Self, Stderr>
+    where
+        Value: fmt::Debug,
+        Output: fmt::Debug,
+    {
+        self.dbg_to(Stderr, label)
+    }
+
+    /// Like `dbg`, printing only the output state `O` so the value does not need to be `Debug`.
+    #[cfg(feature = "std")]
+    #[track_caller]
+    fn dbg_state(self, label: &'static str) -> DbgState<Self, Stderr>
+    where
+        Output: fmt::Debug,
+    {
+        self.dbg_state_to(Stderr, label)
+    }
+
+    /// Attaches a label to this structure, which is passed to the observer given to
+    /// `run_with_observer` when this structure runs. The value and state are unchanged.
+    fn named(self, label: &'static str) -> Labelled<Self> {
+        Labelled { first: self, label }
+    }
+
+    /// Measures how long this structure takes to run, returning the elapsed `Duration` alongside the value of `A`.
+    #[cfg(feature = "std")]
+    fn timed(self) -> Timed<Self, fn() -> Instant> {
+        self.timed_with(Instant::now)
+    }
+
+    /// Like `timed`, reading the time from `clock` before and after this structure runs.
+    #[cfg(feature = "std")]
+    fn timed_with<Clock>(self, clock: Clock) -> Timed<Self, Clock>
+    where
+        Clock: FnMut() -> Instant,
+    {
+        Timed { first: self, clock }
+    }
+
+    /// Erases the type of this structure by boxing it, so structures of different types
+    /// can be stored together or returned from different branches.
+    ///
+    /// This allocates the structure on the heap, and running it goes through a virtual call,
+    /// so prefer the concrete type when only one shape of structure is needed.
+    #[cfg(feature = "alloc")]
+    fn boxed<'a>(self) -> BoxIndexedState<'a, Input, Output, Value>
+    where
+        Self: 'a,
+    {
+        alloc::boxed::Box::new(self)
+    }
+
+    /// Like `boxed`, keeping the boxed structure `Send` so it can be moved to another thread.
+    ///
+    /// ```
+    /// use indexed_state::{IndexedState, pure};
+    ///
+    /// let pipeline = pure(2).map(|n: i32| n * 10).map_state(|s: i32| s + 1).boxed_send();
+    /// let handle = std::thread::spawn(move || pipeline.run(4));
+    /// assert_eq!(handle.join().unwrap(), (20, 5));
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn boxed_send<'a>(self) -> BoxSendIndexedState<'a, Input, Output, Value>
+    where
+        Self: Send + 'a,
+    {
+        alloc::boxed::Box::new(self)
+    }
+
+    /// Lifts this structure into a `TryIndexedState` that never fails.
+    fn infallible(self) -> Fallible<Self> {
+        Fallible { first: self }
+    }
+
+    /// Lifts this structure into a `TryIndexedState` that fails with a `PanicError`
+    /// holding the panic message when this structure panics, instead of unwinding.
+    #[cfg(feature = "std")]
+    fn catch_panic(self) -> CatchPanic<Self>
+    where
+        Self: UnwindSafe,
+        Input: UnwindSafe,
+    {
+        CatchPanic { first: self }
+    }
+
+    /// Like `catch_panic`, cloning the input state before this structure runs
+    /// so it can be returned within the `PanicError`.
+    #[cfg(feature = "std")]
+    fn catch_panic_with_input(self) -> CatchPanicWithInput<Self>
+    where
+        Self: UnwindSafe,
+        Input: Clone + UnwindSafe,
+    {
+        CatchPanicWithInput { first: self }
+    }
+}
+
+/// Every `FnOnce(I) -> (A, O)` is a `Stateful` structure.
+///
+/// This includes borrowed closures, as `&F` is `FnOnce` when `F` is `Fn` and `&mut F` is `FnOnce` when `F` is `FnMut`,
+/// so `(&step).map(..)` composes a closure without moving it.
+impl<FirstInput, SecondInput, FirstValue, Covariant>
+    IndexedState<FirstInput, SecondInput, FirstValue> for Covariant
+where
+    Covariant: FnOnce(FirstInput) -> (FirstValue, SecondInput),
+{
+    fn run(self, state: FirstInput) -> (FirstValue, SecondInput) {
+        self(state)
+    }
+}
+
+/// Wraps a function from the input state to a value and output state in a named structure,
+/// so it can be stored in struct fields and cloned when the function allows it.
+pub fn state<Input, Output, Value, Function>(function: Function) -> State<Function>
+where
+    Function: FnOnce(Input) -> (Value, Output),
+{
+    State(function)
+}
+
+/// Create a `Stateful` structure given the input type can be cloned.
+pub fn new<Input>() -> New<Input>
+where
+    Input: Clone,
+{
+    New {
+        phantom: PhantomData,
+    }
+}
+
+/// Applies a function to the input state, returning the result as both the value and the output state.
+pub fn gets<Input, Output, Covariant>(covariant: Covariant) -> Gets<Covariant>
+where
+    Covariant: FnOnce(Input) -> Output,
+    Output: Clone,
+{
+    Gets { covariant }
+}
+
+/// Applies a function to a clone of the input state to create the value, returning the state untouched.
+pub fn gots<Input, Value, Covariant>(covariant: Covariant) -> Gots<Covariant>
+where
+    Covariant: FnOnce(Input) -> Value,
+    Input: Clone,
+{
+    Gots { covariant }
+}
+
+/// Like `gots`, but borrows the state for the projection instead of cloning it.
+pub fn gots_ref<Input, Value, Covariant>(covariant: Covariant) -> GotsRef<Covariant>
+where
+    Covariant: FnOnce(&Input) -> Value,
+{
+    GotsRef { covariant }
+}
+
+/// Lifts `value` into a structure that returns the state untouched.
+/// This is the unit for `and_then`.
+///
+/// ```
+/// use indexed_state::{gots, pure, IndexedState};
+///
+/// let state = pure(3).and_then(|n| gots(move |s: i32| s + n));
+/// assert_eq!(state.run(4), (7, 4));
+/// ```
+pub fn pure<Input, Value>(value: Value) -> Pure<Input, Value> {
+    Pure {
+        value,
+        phantom: PhantomData,
+    }
+}
+
+/// Discards the input state and replaces it with `state`, returning `()` as the value.
+/// The input and output states may be different types.
+pub fn put<Input, Output>(state: Output) -> Put<Input, Output> {
+    Put {
+        state,
+        phantom: PhantomData,
+    }
+}
+
+/// Replaces the input state with `state`, returning the previous state as the value.
+pub fn exchange<Input, Output>(state: Output) -> Exchange<Input, Output> {
+    Exchange {
+        state,
+        phantom: PhantomData,
+    }
+}
+
+/// Flips the components of a tuple shaped state, returning `()` as the value.
+pub fn swap() -> Swap {
+    Swap
+}
+
+/// Applies a function to the input state to create the output state, returning `()` as the value.
+pub fn modify<Input, Output, Covariant>(covariant: Covariant) -> Modify<Covariant>
+where
+    Covariant: FnOnce(Input) -> Output,
+{
+    Modify { covariant }
+}
+
+/// Creates an index changing transition from `I` to `O`, returning `()` as the value.
+pub fn imodify<Input, Output, Covariant>(covariant: Covariant) -> IModify<Covariant>
+where
+    Covariant: FnOnce(Input) -> Output,
+{
+    IModify { covariant }
+}
+
+/// Creates an index changing transition from `I` to `O` that also returns a value,
+/// such as the event emitted by a state machine step.
+pub fn itransition<Input, Output, Value, Transition>(
+    transition: Transition,
+) -> ITransition<Transition>
+where
+    Transition: FnOnce(Input) -> (Value, Output),
+{
+    ITransition { transition }
+}
+
+/// Ignores the input state, returning the precomputed value and output state.
+pub fn from_pair<Value, Output>((value, state): (Value, Output)) -> Constant<Value, Output> {
+    Constant { value, state }
+}
+
+/// Delays constructing a stateful computation until it is run.
+/// The thunk is only called when the state is provided.
+pub fn defer<Input, Output, Value, Thunk, Second>(thunk: Thunk) -> Defer<Thunk>
+where
+    Thunk: FnOnce() -> Second,
+    Second: IndexedState<Input, Output, Value>,
+{
+    Defer { thunk }
+}
+
+/// Runs `acquire` to create a resource, then the structure returned by `body` which borrows the resource,
+/// and finally the structure returned by `release` which consumes the resource.
+///
+/// The state is threaded through all three phases in that order.
+/// The value of `body` is returned and the value of `release` is discarded.
+pub fn bracket<
+    Input,
+    FirstOutput,
+    SecondOutput,
+    ThirdOutput,
+    Resource,
+    Value,
+    ReleaseValue,
+    Acquire,
+    Body,
+    Release,
+    Second,
+    Third,
+>(
+    acquire: Acquire,
+    body: Body,
+    release: Release,
+) -> Bracket<Acquire, Body, Release, (Resource, FirstOutput, SecondOutput, ReleaseValue)>
+where
+    Acquire: IndexedState<Input, FirstOutput, Resource>,
+    Body: FnOnce(&Resource) -> Second,
+    Second: IndexedState<FirstOutput, SecondOutput, Value>,
+    Release: FnOnce(Resource) -> Third,
+    Third: IndexedState<SecondOutput, ThirdOutput, ReleaseValue>,
+{
+    Bracket {
+        acquire,
+        body,
+        release,
+        phantom: PhantomData,
+    }
+}
+
+/// Runs the structure created by `factory` `count` times, threading the state through each run
+/// and folding every value into `init` with `fold`.
+///
+/// The factory is given the index of the run, and a `count` of `0` returns `init` with the input state untouched.
+pub fn repeat_fold<State, Value, Accumulator, Factory, Step, Fold>(
+    factory: Factory,
+    count: usize,
+    init: Accumulator,
+    fold: Fold,
+) -> RepeatFold<Factory, Accumulator, Fold, Value>
+where
+    Factory: FnMut(usize) -> Step,
+    Step: IndexedState<State, State, Value>,
+    Fold: FnMut(Accumulator, Value) -> Accumulator,
+{
+    RepeatFold {
+        factory,
+        count,
+        init,
+        fold,
+        phantom: PhantomData,
+    }
+}
+
+/// Applies the structure created by `step` for every item of `items` in order,
+/// threading the state through each of them.
+///
+/// An empty `items` returns the input state untouched.
+pub fn for_each_item<State, Items, Step, Second>(
+    items: Items,
+    step: Step,
+) -> ForEachItem<Items, Step>
+where
+    Items: IntoIterator,
+    Step: FnMut(Items::Item) -> Second,
+    Second: IndexedState<State, State, ()>,
+{
+    ForEachItem { items, step }
+}
+
+/// Runs the structure created by `factory` repeatedly, threading the state through each run,
+/// for as long as `predicate` returns `true` for its value.
+///
+/// The structure is always run at least once and at most `limit` times.
+/// When `predicate` still holds after the last run,
+/// the last value is returned as `Bounded::Exceeded` instead of `Bounded::Finished`.
+///
+/// # Panics
+///
+/// Panics when `limit` is `0`, as there would be no value to return.
+pub fn loop_while<State, Value, Factory, Step, Predicate>(
+    factory: Factory,
+    predicate: Predicate,
+    limit: usize,
+) -> LoopWhile<Factory, Predicate>
+where
+    Factory: FnMut() -> Step,
+    Step: IndexedState<State, State, Value>,
+    Predicate: FnMut(&Value) -> bool,
+{
+    assert!(limit > 0, "the limit of loop_while must be at least 1");
+    LoopWhile {
+        factory,
+        predicate,
+        limit,
+    }
+}
+
+/// Runs the structure created by `factory` repeatedly, threading the state through each run,
+/// until `predicate` returns `true` for the state, returning the number of runs as the value.
+///
+/// The predicate is checked before every run, so a state that already satisfies it is returned untouched.
+/// When the state still does not satisfy it after `limit` runs,
+/// the count is returned as `Bounded::Exceeded` instead of `Bounded::Finished`.
+pub fn repeat_until<State, Value, Factory, Step, Predicate>(
+    factory: Factory,
+    predicate: Predicate,
+    limit: usize,
+) -> RepeatUntil<Factory, Predicate, Value>
+where
+    Factory: FnMut() -> Step,
+    Step: IndexedState<State, State, Value>,
+    Predicate: FnMut(&State) -> bool,
+{
+    RepeatUntil {
+        factory,
+        predicate,
+        limit,
+        phantom: PhantomData,
+    }
+}
+
+/// Runs the structure returned by `body` in a loop, threading the state and the accumulator through each run,
+/// until it returns `ControlFlow::Break`, whose value is returned.
+///
+/// The loop runs without recursion, so the stack does not grow with the number of runs.
+pub fn loop_<State, Accumulator, Done, Body, Step>(
+    init: Accumulator,
+    body: Body,
+) -> Loop<Accumulator, Body>
+where
+    Body: FnMut(Accumulator) -> Step,
+    Step: IndexedState<State, State, ControlFlow<Done, Accumulator>>,
+{
+    Loop { init, body }
+}
+
+/// Like `for_each_item`, also calling `on_chunk` with the state and the number of items processed so far
+/// after every `chunk_size` items, and once more after any remaining items.
+///
+/// The chunks do not change how the state is threaded, so the output state is the same as `for_each_item`.
+/// A `chunk_size` of `0` is treated as `1`.
+pub fn for_each_chunked<State, Items, Step, Second, OnChunk>(
+    items: Items,
+    chunk_size: usize,
+    step: Step,
+    on_chunk: OnChunk,
+) -> ForEachChunked<Items, Step, OnChunk>
+where
+    Items: IntoIterator,
+    Step: FnMut(Items::Item) -> Second,
+    Second: IndexedState<State, State, ()>,
+    OnChunk: FnMut(&State, usize),
+{
+    ForEachChunked {
+        items,
+        chunk_size,
+        step,
+        on_chunk,
+    }
+}
+
+/// Applies the structure created by `step` for the items of `items` in order, threading the state through each of them,
+/// until one of them returns `ControlFlow::Break`.
+///
+/// The value is the `Break` value, or `None` when `items` ran out first,
+/// alongside the iterator of the items that were not consumed so they can be resumed later.
+pub fn drain_until<State, Done, Items, Step, Second>(
+    items: Items,
+    step: Step,
+) -> DrainUntil<Items, Step>
+where
+    Items: IntoIterator,
+    Step: FnMut(Items::Item) -> Second,
+    Second: IndexedState<State, State, ControlFlow<Done>>,
+{
+    DrainUntil { items, step }
+}
+
+/// Rebuilds a state from `initial` by running the structure returned by `transition` for every event in order,
+/// returning the effects of every event extended into one buffer alongside the final state.
+///
+/// The buffer can be any collection that is `Default` and `Extend`, such as a `Vec`.
+/// An empty `events` returns an empty buffer with `initial` untouched.
+pub fn fold_events<State, Event, Effects, Buffer, Events, Transition, Second>(
+    initial: State,
+    events: Events,
+    mut transition: Transition,
+) -> (Buffer, State)
+where
+    Events: IntoIterator<Item = Event>,
+    Transition: FnMut(&Event) -> Second,
+    Second: IndexedState<State, State, Effects>,
+    Effects: IntoIterator,
+    Buffer: Default + Extend<Effects::Item>,
+{
+    let mut buffer = Buffer::default();
+    let mut state = initial;
+    for event in events {
+        let (effects, o) = transition(&event).run(state);
+        buffer.extend(effects);
+        state = o;
+    }
+    (buffer, state)
+}
+
+/// A stateful computation backed by a function from the input state to a value and output state.
+#[derive(Clone, Copy)]
+pub struct State<Function>(pub Function);
+
+impl<Function> fmt::Debug for State<Function> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("State").field(&Closure).finish()
+    }
+}
+
+impl<Input, Output, Value, Function> IndexedState<Input, Output, Value> for State<Function>
+where
+    Function: FnOnce(Input) -> (Value, Output),
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        (self.0)(state)
+    }
+}
+
+pub struct New<Input> {
+    phantom: PhantomData<fn() -> Input>,
+}
+
+impl<Input> fmt::Debug for New<Input> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("New").finish()
+    }
+}
+
+impl<Input> Clone for New<Input> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Input> Copy for New<Input> {}
+
+impl<Input> IndexedState<Input, Input, Input> for New<Input>
+where
+    Input: Clone,
+{
+    fn run(self, state: Input) -> (Input, Input) {
+        (state.clone(), state)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Gets<Covariant> {
+    covariant: Covariant,
+}
+
+impl<Covariant> fmt::Debug for Gets<Covariant> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Gets").field("covariant", &Closure).finish()
+    }
+}
+
+impl<Input, Output, Covariant> IndexedState<Input, Output, Output> for Gets<Covariant>
+where
+    Covariant: FnOnce(Input) -> Output,
+    Output: Clone,
+{
+    fn run(self, state: Input) -> (Output, Output) {
+        let output = (self.covariant)(state);
+        (output.clone(), output)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Gots<Covariant> {
+    covariant: Covariant,
+}
+
+impl<Covariant> fmt::Debug for Gots<Covariant> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Gots").field("covariant", &Closure).finish()
+    }
+}
+
+impl<Input, Value, Covariant> IndexedState<Input, Input, Value> for Gots<Covariant>
+where
+    Covariant: FnOnce(Input) -> Value,
+    Input: Clone,
+{
+    fn run(self, state: Input) -> (Value, Input) {
+        let value = (self.covariant)(state.clone());
+        (value, state)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct GotsRef<Covariant> {
+    covariant: Covariant,
+}
+
+impl<Covariant> fmt::Debug for GotsRef<Covariant> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GotsRef")
+            .field("covariant", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Value, Covariant> IndexedState<Input, Input, Value> for GotsRef<Covariant>
+where
+    Covariant: FnOnce(&Input) -> Value,
+{
+    fn run(self, state: Input) -> (Value, Input) {
+        let value = (self.covariant)(&state);
+        (value, state)
+    }
+}
+
+pub struct Pure<Input, Value> {
+    value: Value,
+    phantom: PhantomData<fn() -> Input>,
+}
+
+impl<Input, Value> fmt::Debug for Pure<Input, Value>
+where
+    Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pure").field("value", &self.value).finish()
+    }
+}
+
+impl<Input, Value> Clone for Pure<Input, Value>
+where
+    Value: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Input, Value> Copy for Pure<Input, Value> where Value: Copy {}
+
+impl<Input, Value> IndexedState<Input, Input, Value> for Pure<Input, Value> {
+    fn run(self, state: Input) -> (Value, Input) {
+        (self.value, state)
+    }
+}
+
+pub struct Put<Input, Output> {
+    state: Output,
+    phantom: PhantomData<fn() -> Input>,
+}
+
+impl<Input, Output> fmt::Debug for Put<Input, Output>
+where
+    Output: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Put").field("state", &self.state).finish()
+    }
+}
+
+impl<Input, Output> Clone for Put<Input, Output>
+where
+    Output: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Input, Output> Copy for Put<Input, Output> where Output: Copy {}
+
+impl<Input, Output> IndexedState<Input, Output, ()> for Put<Input, Output> {
+    fn run(self, _: Input) -> ((), Output) {
+        ((), self.state)
+    }
+}
+
+pub struct Exchange<Input, Output> {
+    state: Output,
+    phantom: PhantomData<fn() -> Input>,
+}
+
+impl<Input, Output> fmt::Debug for Exchange<Input, Output>
+where
+    Output: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Exchange")
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<Input, Output> Clone for Exchange<Input, Output>
+where
+    Output: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Input, Output> Copy for Exchange<Input, Output> where Output: Copy {}
+
+impl<Input, Output> IndexedState<Input, Output, Input> for Exchange<Input, Output> {
+    fn run(self, previous: Input) -> (Input, Output) {
+        (previous, self.state)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Modify<Covariant> {
+    covariant: Covariant,
+}
+
+impl<Covariant> fmt::Debug for Modify<Covariant> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Modify")
+            .field("covariant", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Covariant> IndexedState<Input, Output, ()> for Modify<Covariant>
+where
+    Covariant: FnOnce(Input) -> Output,
+{
+    fn run(self, state: Input) -> ((), Output) {
+        ((), (self.covariant)(state))
+    }
+}
+
+pub struct Map<First, Covariant, Phantom> {
+    first: First,
+    covariant: Covariant,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Covariant, Phantom> fmt::Debug for Map<First, Covariant, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Map")
+            .field("first", &self.first)
+            .field("covariant", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Covariant, Phantom> Clone for Map<First, Covariant, Phantom>
+where
+    First: Clone,
+    Covariant: Clone,
+{
+    fn clone(&self) -> Self {
+        Map {
+            first: self.first.clone(),
+            covariant: self.covariant.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Covariant, Phantom> Copy for Map<First, Covariant, Phantom>
+where
+    First: Copy,
+    Covariant: Copy,
+{
+}
+
+impl<FirstInput, SecondInput, SecondValue, First, Covariant, FirstValue>
+    IndexedState<FirstInput, SecondInput, SecondValue> for Map<First, Covariant, FirstValue>
+where
+    First: IndexedState<FirstInput, SecondInput, FirstValue>,
+    Covariant: FnOnce(FirstValue) -> SecondValue,
+{
+    fn run(self, state: FirstInput) -> (SecondValue, SecondInput) {
+        let (a, o) = self.first.run(state);
+        let b = (self.covariant)(a);
+        (b, o)
+    }
+}
+
+pub struct MapState<First, Covariant, Phantom> {
+    first: First,
+    covariant: Covariant,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Covariant, Phantom> fmt::Debug for MapState<First, Covariant, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapState")
+            .field("first", &self.first)
+            .field("covariant", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Covariant, Phantom> Clone for MapState<First, Covariant, Phantom>
+where
+    First: Clone,
+    Covariant: Clone,
+{
+    fn clone(&self) -> Self {
+        MapState {
+            first: self.first.clone(),
+            covariant: self.covariant.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Covariant, Phantom> Copy for MapState<First, Covariant, Phantom>
+where
+    First: Copy,
+    Covariant: Copy,
+{
+}
+
+impl<FirstInput, SecondInput, SecondOutput, First, Function, FirstValue>
+    IndexedState<FirstInput, SecondOutput, FirstValue> for MapState<First, Function, SecondInput>
+where
+    First: IndexedState<FirstInput, SecondInput, FirstValue>,
+    Function: FnOnce(SecondInput) -> SecondOutput,
+{
+    fn run(self, state: FirstInput) -> (FirstValue, SecondOutput) {
+        let (a, o) = self.first.run(state);
+        let p = (self.covariant)(o);
+        (a, p)
+    }
+}
+
+pub struct AndThen<First, Kleisli, Phantom> {
+    stateful: First,
+    kleisli: Kleisli,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Kleisli, Phantom> fmt::Debug for AndThen<First, Kleisli, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AndThen")
+            .field("stateful", &self.stateful)
+            .field("kleisli", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Kleisli, Phantom> Clone for AndThen<First, Kleisli, Phantom>
+where
+    First: Clone,
+    Kleisli: Clone,
+{
+    fn clone(&self) -> Self {
+        AndThen {
+            stateful: self.stateful.clone(),
+            kleisli: self.kleisli.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Kleisli, Phantom> Copy for AndThen<First, Kleisli, Phantom>
+where
+    First: Copy,
+    Kleisli: Copy,
+{
+}
+
+impl<FirstInput, SecondOutput, FirstValue, First, Kleisli, SecondInput, Second, SecondValue>
+    IndexedState<FirstInput, SecondOutput, SecondValue>
+    for AndThen<First, Kleisli, (FirstValue, SecondInput)>
+where
+    First: IndexedState<FirstInput, SecondInput, FirstValue>,
+    Second: IndexedState<SecondInput, SecondOutput, SecondValue>,
+    Kleisli: FnOnce(FirstValue) -> Second,
+{
+    fn run(self, state: FirstInput) -> (SecondValue, SecondOutput) {
+        let (a, o) = self.stateful.run(state);
+        (self.kleisli)(a).run(o)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ContramapState<First, Contravariant> {
+    first: First,
+    contravariant: Contravariant,
+}
+
+impl<First, Contravariant> fmt::Debug for ContramapState<First, Contravariant>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContramapState")
+            .field("first", &self.first)
+            .field("contravariant", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, PreviousInput, Second, Contravariant>
+    IndexedState<PreviousInput, Output, Value> for ContramapState<Second, Contravariant>
+where
+    Second: IndexedState<Input, Output, Value>,
+    Contravariant: FnOnce(PreviousInput) -> Input,
+{
+    fn run(self, state: PreviousInput) -> (Value, Output) {
+        let state = (self.contravariant)(state);
+        self.first.run(state)
+    }
+}
+
+pub struct Apply<First, Second, Phantom> {
+    first: First,
+    second: Second,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Second, Phantom> fmt::Debug for Apply<First, Second, Phantom>
+where
+    First: fmt::Debug,
+    Second: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Apply")
+            .field("first", &self.first)
+            .field("second", &self.second)
+            .finish()
+    }
+}
+
+impl<First, Second, Phantom> Clone for Apply<First, Second, Phantom>
+where
+    First: Clone,
+    Second: Clone,
+{
+    fn clone(&self) -> Self {
+        Apply {
+            first: self.first.clone(),
+            second: self.second.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Second, Phantom> Copy for Apply<First, Second, Phantom>
+where
+    First: Copy,
+    Second: Copy,
+{
+}
+
+impl<FirstInput, SecondInput, SecondOutput, FirstValue, Covariant, First, Second, SecondValue>
+    IndexedState<FirstInput, SecondOutput, SecondValue>
+    for Apply<First, Second, (SecondInput, FirstValue, Covariant)>
+where
+    First: IndexedState<FirstInput, SecondInput, Covariant>,
+    Second: IndexedState<SecondInput, SecondOutput, FirstValue>,
+    Covariant: FnOnce(FirstValue) -> SecondValue,
+{
+    fn run(self, state: FirstInput) -> (SecondValue, SecondOutput) {
+        let (f, state) = self.first.run(state);
+        let (a, p) = self.second.run(state);
+        let b = f(a);
+        (b, p)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct IModify<Covariant> {
+    covariant: Covariant,
+}
+
+impl<Covariant> fmt::Debug for IModify<Covariant> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IModify")
+            .field("covariant", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Covariant> IndexedState<Input, Output, ()> for IModify<Covariant>
+where
+    Covariant: FnOnce(Input) -> Output,
+{
+    fn run(self, state: Input) -> ((), Output) {
+        ((), (self.covariant)(state))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ITransition<Transition> {
+    transition: Transition,
+}
+
+impl<Transition> fmt::Debug for ITransition<Transition> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ITransition")
+            .field("transition", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, Transition> IndexedState<Input, Output, Value>
+    for ITransition<Transition>
+where
+    Transition: FnOnce(Input) -> (Value, Output),
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        (self.transition)(state)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Defer<Thunk> {
+    thunk: Thunk,
+}
+
+impl<Thunk> fmt::Debug for Defer<Thunk> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Defer").field("thunk", &Closure).finish()
+    }
+}
+
+impl<Input, Output, Value, Thunk, Second> IndexedState<Input, Output, Value> for Defer<Thunk>
+where
+    Thunk: FnOnce() -> Second,
+    Second: IndexedState<Input, Output, Value>,
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        (self.thunk)().run(state)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Constant<Value, Output> {
+    value: Value,
+    state: Output,
+}
+
+impl<Value, Output> fmt::Debug for Constant<Value, Output>
+where
+    Value: fmt::Debug,
+    Output: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Constant")
+            .field("value", &self.value)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value> IndexedState<Input, Output, Value> for Constant<Value, Output> {
+    fn run(self, _: Input) -> (Value, Output) {
+        (self.value, self.state)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Swap;
+
+impl<First, Second> IndexedState<(First, Second), (Second, First), ()> for Swap {
+    fn run(self, (first, second): (First, Second)) -> ((), (Second, First)) {
+        ((), (second, first))
+    }
+}
+
+pub struct Replace<First, NewValue, Phantom> {
+    first: First,
+    value: NewValue,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, NewValue, Phantom> fmt::Debug for Replace<First, NewValue, Phantom>
+where
+    First: fmt::Debug,
+    NewValue: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Replace")
+            .field("first", &self.first)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl<First, NewValue, Phantom> Clone for Replace<First, NewValue, Phantom>
+where
+    First: Clone,
+    NewValue: Clone,
+{
+    fn clone(&self) -> Self {
+        Replace {
+            first: self.first.clone(),
+            value: self.value.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, NewValue, Phantom> Copy for Replace<First, NewValue, Phantom>
+where
+    First: Copy,
+    NewValue: Copy,
+{
+}
+
+impl<Input, Output, Value, NewValue, First> IndexedState<Input, Output, NewValue>
+    for Replace<First, NewValue, Value>
+where
+    First: IndexedState<Input, Output, Value>,
+{
+    fn run(self, state: Input) -> (NewValue, Output) {
+        let (_, o) = self.first.run(state);
+        (self.value, o)
+    }
+}
+
+pub struct Void<First, Phantom> {
+    first: First,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Phantom> fmt::Debug for Void<First, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Void").field("first", &self.first).finish()
+    }
+}
+
+impl<First, Phantom> Clone for Void<First, Phantom>
+where
+    First: Clone,
+{
+    fn clone(&self) -> Self {
+        Void {
+            first: self.first.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Phantom> Copy for Void<First, Phantom> where First: Copy {}
+
+impl<Input, Output, Value, First> IndexedState<Input, Output, ()> for Void<First, Value>
+where
+    First: IndexedState<Input, Output, Value>,
+{
+    fn run(self, state: Input) -> ((), Output) {
+        let (_, o) = self.first.run(state);
+        ((), o)
+    }
+}
+
+pub struct Zip<First, Second, Phantom> {
+    first: First,
+    second: Second,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Second, Phantom> fmt::Debug for Zip<First, Second, Phantom>
+where
+    First: fmt::Debug,
+    Second: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Zip")
+            .field("first", &self.first)
+            .field("second", &self.second)
+            .finish()
+    }
+}
+
+impl<First, Second, Phantom> Clone for Zip<First, Second, Phantom>
+where
+    First: Clone,
+    Second: Clone,
+{
+    fn clone(&self) -> Self {
+        Zip {
+            first: self.first.clone(),
+            second: self.second.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Second, Phantom> Copy for Zip<First, Second, Phantom>
+where
+    First: Copy,
+    Second: Copy,
+{
+}
+
+impl<FirstInput, SecondInput, SecondOutput, FirstValue, SecondValue, First, Second>
+    IndexedState<FirstInput, SecondOutput, (FirstValue, SecondValue)>
+    for Zip<First, Second, (FirstValue, SecondInput)>
+where
+    First: IndexedState<FirstInput, SecondInput, FirstValue>,
+    Second: IndexedState<SecondInput, SecondOutput, SecondValue>,
+{
+    fn run(self, state: FirstInput) -> ((FirstValue, SecondValue), SecondOutput) {
+        let (a, o) = self.first.run(state);
+        let (b, p) = self.second.run(o);
+        ((a, b), p)
+    }
+}
+
+pub struct ZipWith<First, Second, Covariant, Phantom> {
+    first: First,
+    second: Second,
+    covariant: Covariant,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Second, Covariant, Phantom> fmt::Debug for ZipWith<First, Second, Covariant, Phantom>
+where
+    First: fmt::Debug,
+    Second: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZipWith")
+            .field("first", &self.first)
+            .field("second", &self.second)
+            .field("covariant", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Second, Covariant, Phantom> Clone for ZipWith<First, Second, Covariant, Phantom>
+where
+    First: Clone,
+    Second: Clone,
+    Covariant: Clone,
+{
+    fn clone(&self) -> Self {
+        ZipWith {
+            first: self.first.clone(),
+            second: self.second.clone(),
+            covariant: self.covariant.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Second, Covariant, Phantom> Copy for ZipWith<First, Second, Covariant, Phantom>
+where
+    First: Copy,
+    Second: Copy,
+    Covariant: Copy,
+{
+}
+
+impl<
+        FirstInput,
+        SecondInput,
+        SecondOutput,
+        FirstValue,
+        SecondValue,
+        ThirdValue,
+        First,
+        Second,
+        Covariant,
+    > IndexedState<FirstInput, SecondOutput, ThirdValue>
+    for ZipWith<First, Second, Covariant, (FirstValue, SecondInput, SecondValue)>
+where
+    First: IndexedState<FirstInput, SecondInput, FirstValue>,
+    Second: IndexedState<SecondInput, SecondOutput, SecondValue>,
+    Covariant: FnOnce(FirstValue, SecondValue) -> ThirdValue,
+{
+    fn run(self, state: FirstInput) -> (ThirdValue, SecondOutput) {
+        let (a, o) = self.first.run(state);
+        let (b, p) = self.second.run(o);
+        let c = (self.covariant)(a, b);
+        (c, p)
+    }
+}
+
+pub struct Zip3<First, Second, Third, Phantom> {
+    first: First,
+    second: Second,
+    third: Third,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Second, Third, Phantom> fmt::Debug for Zip3<First, Second, Third, Phantom>
+where
+    First: fmt::Debug,
+    Second: fmt::Debug,
+    Third: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Zip3")
+            .field("first", &self.first)
+            .field("second", &self.second)
+            .field("third", &self.third)
+            .finish()
+    }
+}
+
+impl<First, Second, Third, Phantom> Clone for Zip3<First, Second, Third, Phantom>
+where
+    First: Clone,
+    Second: Clone,
+    Third: Clone,
+{
+    fn clone(&self) -> Self {
+        Zip3 {
+            first: self.first.clone(),
+            second: self.second.clone(),
+            third: self.third.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Second, Third, Phantom> Copy for Zip3<First, Second, Third, Phantom>
+where
+    First: Copy,
+    Second: Copy,
+    Third: Copy,
+{
+}
+
+impl<
+        FirstInput,
+        SecondInput,
+        SecondOutput,
+        ThirdOutput,
+        FirstValue,
+        SecondValue,
+        ThirdValue,
+        First,
+        Second,
+        Third,
+    > IndexedState<FirstInput, ThirdOutput, (FirstValue, SecondValue, ThirdValue)>
+    for Zip3<First, Second, Third, (FirstValue, SecondInput, SecondValue, SecondOutput)>
+where
+    First: IndexedState<FirstInput, SecondInput, FirstValue>,
+    Second: IndexedState<SecondInput, SecondOutput, SecondValue>,
+    Third: IndexedState<SecondOutput, ThirdOutput, ThirdValue>,
+{
+    fn run(self, state: FirstInput) -> ((FirstValue, SecondValue, ThirdValue), ThirdOutput) {
+        let (a, o) = self.first.run(state);
+        let (b, p) = self.second.run(o);
+        let (c, q) = self.third.run(p);
+        ((a, b, c), q)
+    }
+}
+
+/// Runs each structure in order, threading the state through all of them
+/// and returning the values as a flat tuple.
+#[macro_export]
+macro_rules! zip {
+    ($a:expr, $b:expr $(,)?) => {
+        $crate::IndexedState::zip($a, $b)
+    };
+    ($a:expr, $b:expr, $c:expr $(,)?) => {
+        $crate::IndexedState::zip3($a, $b, $c)
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr $(,)?) => {
+        $crate::IndexedState::map(
+            $crate::IndexedState::zip($crate::IndexedState::zip3($a, $b, $c), $d),
+            |((a, b, c), d)| (a, b, c, d),
+        )
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr, $e:expr $(,)?) => {
+        $crate::IndexedState::map(
+            $crate::IndexedState::zip3($crate::IndexedState::zip3($a, $b, $c), $d, $e),
+            |((a, b, c), d, e)| (a, b, c, d, e),
+        )
+    };
+    ($a:expr, $b:expr, $c:expr, $d:expr, $e:expr, $f:expr $(,)?) => {
+        $crate::IndexedState::map(
+            $crate::IndexedState::zip(
+                $crate::IndexedState::zip3($a, $b, $c),
+                $crate::IndexedState::zip3($d, $e, $f),
+            ),
+            |((a, b, c), (d, e, f))| (a, b, c, d, e, f),
+        )
+    };
+}
+
+pub struct Then<First, Second, Phantom> {
+    first: First,
+    second: Second,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Second, Phantom> fmt::Debug for Then<First, Second, Phantom>
+where
+    First: fmt::Debug,
+    Second: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Then")
+            .field("first", &self.first)
+            .field("second", &self.second)
+            .finish()
+    }
+}
+
+impl<First, Second, Phantom> Clone for Then<First, Second, Phantom>
+where
+    First: Clone,
+    Second: Clone,
+{
+    fn clone(&self) -> Self {
+        Then {
+            first: self.first.clone(),
+            second: self.second.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Second, Phantom> Copy for Then<First, Second, Phantom>
+where
+    First: Copy,
+    Second: Copy,
+{
+}
+
+impl<FirstInput, SecondInput, SecondOutput, FirstValue, SecondValue, First, Second>
+    IndexedState<FirstInput, SecondOutput, SecondValue>
+    for Then<First, Second, (FirstValue, SecondInput)>
+where
+    First: IndexedState<FirstInput, SecondInput, FirstValue>,
+    Second: IndexedState<SecondInput, SecondOutput, SecondValue>,
+{
+    fn run(self, state: FirstInput) -> (SecondValue, SecondOutput) {
+        let (_, o) = self.first.run(state);
+        self.second.run(o)
+    }
+}
+
+pub struct ZipLeft<First, Second, Phantom> {
+    first: First,
+    second: Second,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Second, Phantom> fmt::Debug for ZipLeft<First, Second, Phantom>
+where
+    First: fmt::Debug,
+    Second: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZipLeft")
+            .field("first", &self.first)
+            .field("second", &self.second)
+            .finish()
+    }
+}
+
+impl<First, Second, Phantom> Clone for ZipLeft<First, Second, Phantom>
+where
+    First: Clone,
+    Second: Clone,
+{
+    fn clone(&self) -> Self {
+        ZipLeft {
+            first: self.first.clone(),
+            second: self.second.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Second, Phantom> Copy for ZipLeft<First, Second, Phantom>
+where
+    First: Copy,
+    Second: Copy,
+{
+}
+
+impl<FirstInput, SecondInput, SecondOutput, FirstValue, SecondValue, First, Second>
+    IndexedState<FirstInput, SecondOutput, FirstValue>
+    for ZipLeft<First, Second, (SecondInput, SecondValue)>
+where
+    First: IndexedState<FirstInput, SecondInput, FirstValue>,
+    Second: IndexedState<SecondInput, SecondOutput, SecondValue>,
+{
+    fn run(self, state: FirstInput) -> (FirstValue, SecondOutput) {
+        let (a, o) = self.first.run(state);
+        let (_, p) = self.second.run(o);
+        (a, p)
+    }
+}
+
+pub struct ZipRight<First, Second, Phantom> {
+    first: First,
+    second: Second,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Second, Phantom> fmt::Debug for ZipRight<First, Second, Phantom>
+where
+    First: fmt::Debug,
+    Second: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZipRight")
+            .field("first", &self.first)
+            .field("second", &self.second)
+            .finish()
+    }
+}
+
+impl<First, Second, Phantom> Clone for ZipRight<First, Second, Phantom>
+where
+    First: Clone,
+    Second: Clone,
+{
+    fn clone(&self) -> Self {
+        ZipRight {
+            first: self.first.clone(),
+            second: self.second.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Second, Phantom> Copy for ZipRight<First, Second, Phantom>
+where
+    First: Copy,
+    Second: Copy,
+{
+}
+
+impl<FirstInput, SecondInput, SecondOutput, FirstValue, SecondValue, First, Second>
+    IndexedState<FirstInput, SecondOutput, SecondValue>
+    for ZipRight<First, Second, (FirstValue, SecondInput)>
+where
+    First: IndexedState<FirstInput, SecondInput, FirstValue>,
+    Second: IndexedState<SecondInput, SecondOutput, SecondValue>,
+{
+    fn run(self, state: FirstInput) -> (SecondValue, SecondOutput) {
+        let (_, o) = self.first.run(state);
+        self.second.run(o)
+    }
+}
+
+pub struct Flatten<First, Phantom> {
+    first: First,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Phantom> fmt::Debug for Flatten<First, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Flatten")
+            .field("first", &self.first)
+            .finish()
+    }
+}
+
+impl<First, Phantom> Clone for Flatten<First, Phantom>
+where
+    First: Clone,
+{
+    fn clone(&self) -> Self {
+        Flatten {
+            first: self.first.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Phantom> Copy for Flatten<First, Phantom> where First: Copy {}
+
+impl<FirstInput, SecondInput, SecondOutput, SecondValue, First, Second>
+    IndexedState<FirstInput, SecondOutput, SecondValue> for Flatten<First, (Second, SecondInput)>
+where
+    First: IndexedState<FirstInput, SecondInput, Second>,
+    Second: IndexedState<SecondInput, SecondOutput, SecondValue>,
+{
+    fn run(self, state: FirstInput) -> (SecondValue, SecondOutput) {
+        let (second, o) = self.first.run(state);
+        second.run(o)
+    }
+}
+
+pub struct AndThenWithState<First, Kleisli, Phantom> {
+    stateful: First,
+    kleisli: Kleisli,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Kleisli, Phantom> fmt::Debug for AndThenWithState<First, Kleisli, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AndThenWithState")
+            .field("stateful", &self.stateful)
+            .field("kleisli", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Kleisli, Phantom> Clone for AndThenWithState<First, Kleisli, Phantom>
+where
+    First: Clone,
+    Kleisli: Clone,
+{
+    fn clone(&self) -> Self {
+        AndThenWithState {
+            stateful: self.stateful.clone(),
+            kleisli: self.kleisli.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Kleisli, Phantom> Copy for AndThenWithState<First, Kleisli, Phantom>
+where
+    First: Copy,
+    Kleisli: Copy,
+{
+}
+
+impl<FirstInput, SecondOutput, FirstValue, First, Kleisli, SecondInput, Second, SecondValue>
+    IndexedState<FirstInput, SecondOutput, SecondValue>
+    for AndThenWithState<First, Kleisli, (FirstValue, SecondInput)>
+where
+    First: IndexedState<FirstInput, SecondInput, FirstValue>,
+    Second: IndexedState<SecondInput, SecondOutput, SecondValue>,
+    Kleisli: FnOnce(FirstValue, &SecondInput) -> Second,
+{
+    fn run(self, state: FirstInput) -> (SecondValue, SecondOutput) {
+        let (a, o) = self.stateful.run(state);
+        (self.kleisli)(a, &o).run(o)
+    }
+}
+
+pub struct FlatMapState<First, Kleisli, Phantom> {
+    stateful: First,
+    kleisli: Kleisli,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Kleisli, Phantom> fmt::Debug for FlatMapState<First, Kleisli, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlatMapState")
+            .field("stateful", &self.stateful)
+            .field("kleisli", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Kleisli, Phantom> Clone for FlatMapState<First, Kleisli, Phantom>
+where
+    First: Clone,
+    Kleisli: Clone,
+{
+    fn clone(&self) -> Self {
+        FlatMapState {
+            stateful: self.stateful.clone(),
+            kleisli: self.kleisli.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Kleisli, Phantom> Copy for FlatMapState<First, Kleisli, Phantom>
+where
+    First: Copy,
+    Kleisli: Copy,
+{
+}
+
+impl<FirstInput, SecondOutput, FirstValue, First, Kleisli, SecondInput, Second, SecondValue>
+    IndexedState<FirstInput, SecondOutput, FirstValue>
+    for FlatMapState<First, Kleisli, (SecondInput, SecondValue)>
+where
+    First: IndexedState<FirstInput, SecondInput, FirstValue>,
+    Second: IndexedState<SecondInput, SecondOutput, SecondValue>,
+    Kleisli: FnOnce(&SecondInput) -> Second,
+{
+    fn run(self, state: FirstInput) -> (FirstValue, SecondOutput) {
+        let (a, o) = self.stateful.run(state);
+        let (_, p) = (self.kleisli)(&o).run(o);
+        (a, p)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Inspect<First, Function> {
+    first: First,
+    function: Function,
+}
+
+impl<First, Function> fmt::Debug for Inspect<First, Function>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Inspect")
+            .field("first", &self.first)
+            .field("function", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First, Function> IndexedState<Input, Output, Value>
+    for Inspect<First, Function>
+where
+    First: IndexedState<Input, Output, Value>,
+    Function: FnOnce(&Value),
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        let (a, o) = self.first.run(state);
+        (self.function)(&a);
+        (a, o)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct InspectState<First, Function> {
+    first: First,
+    function: Function,
+}
+
+impl<First, Function> fmt::Debug for InspectState<First, Function>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InspectState")
+            .field("first", &self.first)
+            .field("function", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First, Function> IndexedState<Input, Output, Value>
+    for InspectState<First, Function>
+where
+    First: IndexedState<Input, Output, Value>,
+    Function: FnOnce(&Output),
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        let (a, o) = self.first.run(state);
+        (self.function)(&o);
+        (a, o)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct TapMut<First, Function> {
+    first: First,
+    function: Function,
+}
+
+impl<First, Function> fmt::Debug for TapMut<First, Function>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TapMut")
+            .field("first", &self.first)
+            .field("function", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First, Function> IndexedState<Input, Output, Value>
+    for TapMut<First, Function>
+where
+    First: IndexedState<Input, Output, Value>,
+    Function: FnOnce(&mut Value),
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        let (mut a, o) = self.first.run(state);
+        (self.function)(&mut a);
+        (a, o)
+    }
+}
+
+pub struct Bimap<First, Covariant, CovariantState, Phantom> {
+    first: First,
+    covariant: Covariant,
+    covariant_state: CovariantState,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Covariant, CovariantState, Phantom> fmt::Debug
+    for Bimap<First, Covariant, CovariantState, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bimap")
+            .field("first", &self.first)
+            .field("covariant", &Closure)
+            .field("covariant_state", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Covariant, CovariantState, Phantom> Clone
+    for Bimap<First, Covariant, CovariantState, Phantom>
+where
+    First: Clone,
+    Covariant: Clone,
+    CovariantState: Clone,
+{
+    fn clone(&self) -> Self {
+        Bimap {
+            first: self.first.clone(),
+            covariant: self.covariant.clone(),
+            covariant_state: self.covariant_state.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Covariant, CovariantState, Phantom> Copy
+    for Bimap<First, Covariant, CovariantState, Phantom>
+where
+    First: Copy,
+    Covariant: Copy,
+    CovariantState: Copy,
+{
+}
+
+impl<Input, Output, SecondOutput, Value, SecondValue, First, Covariant, CovariantState>
+    IndexedState<Input, SecondOutput, SecondValue>
+    for Bimap<First, Covariant, CovariantState, (Value, Output)>
+where
+    First: IndexedState<Input, Output, Value>,
+    Covariant: FnOnce(Value) -> SecondValue,
+    CovariantState: FnOnce(Output) -> SecondOutput,
+{
+    fn run(self, state: Input) -> (SecondValue, SecondOutput) {
+        let (a, o) = self.first.run(state);
+        let b = (self.covariant)(a);
+        let p = (self.covariant_state)(o);
+        (b, p)
+    }
+}
+
+pub struct MapWithState<First, Covariant, Phantom> {
+    first: First,
+    covariant: Covariant,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Covariant, Phantom> fmt::Debug for MapWithState<First, Covariant, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapWithState")
+            .field("first", &self.first)
+            .field("covariant", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Covariant, Phantom> Clone for MapWithState<First, Covariant, Phantom>
+where
+    First: Clone,
+    Covariant: Clone,
+{
+    fn clone(&self) -> Self {
+        MapWithState {
+            first: self.first.clone(),
+            covariant: self.covariant.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Covariant, Phantom> Copy for MapWithState<First, Covariant, Phantom>
+where
+    First: Copy,
+    Covariant: Copy,
+{
+}
+
+impl<Input, Output, Value, SecondValue, First, Covariant> IndexedState<Input, Output, SecondValue>
+    for MapWithState<First, Covariant, Value>
+where
+    First: IndexedState<Input, Output, Value>,
+    Covariant: FnOnce(Value, &Output) -> SecondValue,
+{
+    fn run(self, state: Input) -> (SecondValue, Output) {
+        let (a, o) = self.first.run(state);
+        let b = (self.covariant)(a, &o);
+        (b, o)
+    }
+}
+
+pub struct MapStateWithValue<First, Covariant, Phantom> {
+    first: First,
+    covariant: Covariant,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Covariant, Phantom> fmt::Debug for MapStateWithValue<First, Covariant, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapStateWithValue")
+            .field("first", &self.first)
+            .field("covariant", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Covariant, Phantom> Clone for MapStateWithValue<First, Covariant, Phantom>
+where
+    First: Clone,
+    Covariant: Clone,
+{
+    fn clone(&self) -> Self {
+        MapStateWithValue {
+            first: self.first.clone(),
+            covariant: self.covariant.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Covariant, Phantom> Copy for MapStateWithValue<First, Covariant, Phantom>
+where
+    First: Copy,
+    Covariant: Copy,
+{
+}
+
+impl<Input, Output, SecondOutput, Value, First, Covariant> IndexedState<Input, SecondOutput, Value>
+    for MapStateWithValue<First, Covariant, Output>
+where
+    First: IndexedState<Input, Output, Value>,
+    Covariant: FnOnce(Output, &Value) -> SecondOutput,
+{
+    fn run(self, state: Input) -> (Value, SecondOutput) {
+        let (a, o) = self.first.run(state);
+        let p = (self.covariant)(o, &a);
+        (a, p)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct WithState<First> {
+    first: First,
+}
+
+impl<First> fmt::Debug for WithState<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithState")
+            .field("first", &self.first)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First> IndexedState<Input, Output, (Value, Output)> for WithState<First>
+where
+    First: IndexedState<Input, Output, Value>,
+    Output: Clone,
+{
+    fn run(self, state: Input) -> ((Value, Output), Output) {
+        let (a, o) = self.first.run(state);
+        ((a, o.clone()), o)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct WithInput<First> {
+    first: First,
+}
+
+impl<First> fmt::Debug for WithInput<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithInput")
+            .field("first", &self.first)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First> IndexedState<Input, Output, (Input, Value)> for WithInput<First>
+where
+    First: IndexedState<Input, Output, Value>,
+    Input: Clone,
+{
+    fn run(self, state: Input) -> ((Input, Value), Output) {
+        let input = state.clone();
+        let (a, o) = self.first.run(state);
+        ((input, a), o)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct SwapChannels<First> {
+    first: First,
+}
+
+impl<First> fmt::Debug for SwapChannels<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SwapChannels")
+            .field("first", &self.first)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First> IndexedState<Input, Value, Output> for SwapChannels<First>
+where
+    First: IndexedState<Input, Output, Value>,
+{
+    fn run(self, state: Input) -> (Output, Value) {
+        let (a, o) = self.first.run(state);
+        (o, a)
+    }
+}
+
+pub struct MapInto<First, Phantom> {
+    first: First,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Phantom> fmt::Debug for MapInto<First, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapInto")
+            .field("first", &self.first)
+            .finish()
+    }
+}
+
+impl<First, Phantom> Clone for MapInto<First, Phantom>
+where
+    First: Clone,
+{
+    fn clone(&self) -> Self {
+        MapInto {
+            first: self.first.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Phantom> Copy for MapInto<First, Phantom> where First: Copy {}
+
+impl<Input, Output, Value, SecondValue, First> IndexedState<Input, Output, SecondValue>
+    for MapInto<First, (Value, SecondValue)>
+where
+    First: IndexedState<Input, Output, Value>,
+    Value: Into<SecondValue>,
+{
+    fn run(self, state: Input) -> (SecondValue, Output) {
+        let (a, o) = self.first.run(state);
+        (a.into(), o)
+    }
+}
+
+pub struct StateInto<First, Phantom> {
+    first: First,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Phantom> fmt::Debug for StateInto<First, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StateInto")
+            .field("first", &self.first)
+            .finish()
+    }
+}
+
+impl<First, Phantom> Clone for StateInto<First, Phantom>
+where
+    First: Clone,
+{
+    fn clone(&self) -> Self {
+        StateInto {
+            first: self.first.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Phantom> Copy for StateInto<First, Phantom> where First: Copy {}
+
+impl<Input, Output, SecondOutput, Value, First> IndexedState<Input, SecondOutput, Value>
+    for StateInto<First, (Output, SecondOutput)>
+where
+    First: IndexedState<Input, Output, Value>,
+    Output: Into<SecondOutput>,
+{
+    fn run(self, state: Input) -> (Value, SecondOutput) {
+        let (a, o) = self.first.run(state);
+        (a, o.into())
+    }
+}
+
+pub struct ApFlipped<First, Functions, Phantom> {
+    first: First,
+    functions: Functions,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Functions, Phantom> fmt::Debug for ApFlipped<First, Functions, Phantom>
+where
+    First: fmt::Debug,
+    Functions: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApFlipped")
+            .field("first", &self.first)
+            .field("functions", &self.functions)
+            .finish()
+    }
+}
+
+impl<First, Functions, Phantom> Clone for ApFlipped<First, Functions, Phantom>
+where
+    First: Clone,
+    Functions: Clone,
+{
+    fn clone(&self) -> Self {
+        ApFlipped {
+            first: self.first.clone(),
+            functions: self.functions.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Functions, Phantom> Copy for ApFlipped<First, Functions, Phantom>
+where
+    First: Copy,
+    Functions: Copy,
+{
+}
+
+impl<
+        FirstInput,
+        SecondInput,
+        SecondOutput,
+        FirstValue,
+        SecondValue,
+        Covariant,
+        First,
+        Functions,
+    > IndexedState<FirstInput, SecondOutput, SecondValue>
+    for ApFlipped<First, Functions, (FirstValue, SecondInput, Covariant)>
+where
+    First: IndexedState<FirstInput, SecondInput, FirstValue>,
+    Functions: IndexedState<SecondInput, SecondOutput, Covariant>,
+    Covariant: FnOnce(FirstValue) -> SecondValue,
+{
+    fn run(self, state: FirstInput) -> (SecondValue, SecondOutput) {
+        let (a, o) = self.first.run(state);
+        let (f, p) = self.functions.run(o);
+        let b = f(a);
+        (b, p)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Provided<First, Input> {
+    first: First,
+    state: Input,
+}
+
+impl<First, Input> fmt::Debug for Provided<First, Input>
+where
+    First: fmt::Debug,
+    Input: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Provided")
+            .field("first", &self.first)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First> IndexedState<(), Output, Value> for Provided<First, Input>
+where
+    First: IndexedState<Input, Output, Value>,
+{
+    fn run(self, _: ()) -> (Value, Output) {
+        self.first.run(self.state)
+    }
+}
+
+pub struct MapFirst<First, Covariant, Phantom> {
+    first: First,
+    covariant: Covariant,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Covariant, Phantom> fmt::Debug for MapFirst<First, Covariant, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapFirst")
+            .field("first", &self.first)
+            .field("covariant", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Covariant, Phantom> Clone for MapFirst<First, Covariant, Phantom>
+where
+    First: Clone,
+    Covariant: Clone,
+{
+    fn clone(&self) -> Self {
+        MapFirst {
+            first: self.first.clone(),
+            covariant: self.covariant.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Covariant, Phantom> Copy for MapFirst<First, Covariant, Phantom>
+where
+    First: Copy,
+    Covariant: Copy,
+{
+}
+
+impl<Input, Output, FirstValue, SecondValue, FirstNext, First, Covariant>
+    IndexedState<Input, Output, (FirstNext, SecondValue)> for MapFirst<First, Covariant, FirstValue>
+where
+    First: IndexedState<Input, Output, (FirstValue, SecondValue)>,
+    Covariant: FnOnce(FirstValue) -> FirstNext,
+{
+    fn run(self, state: Input) -> ((FirstNext, SecondValue), Output) {
+        let ((a, b), o) = self.first.run(state);
+        (((self.covariant)(a), b), o)
+    }
+}
+
+pub struct MapSecond<First, Covariant, Phantom> {
+    first: First,
+    covariant: Covariant,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Covariant, Phantom> fmt::Debug for MapSecond<First, Covariant, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapSecond")
+            .field("first", &self.first)
+            .field("covariant", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Covariant, Phantom> Clone for MapSecond<First, Covariant, Phantom>
+where
+    First: Clone,
+    Covariant: Clone,
+{
+    fn clone(&self) -> Self {
+        MapSecond {
+            first: self.first.clone(),
+            covariant: self.covariant.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Covariant, Phantom> Copy for MapSecond<First, Covariant, Phantom>
+where
+    First: Copy,
+    Covariant: Copy,
+{
+}
+
+impl<Input, Output, FirstValue, SecondValue, SecondNext, First, Covariant>
+    IndexedState<Input, Output, (FirstValue, SecondNext)>
+    for MapSecond<First, Covariant, SecondValue>
+where
+    First: IndexedState<Input, Output, (FirstValue, SecondValue)>,
+    Covariant: FnOnce(SecondValue) -> SecondNext,
+{
+    fn run(self, state: Input) -> ((FirstValue, SecondNext), Output) {
+        let ((a, b), o) = self.first.run(state);
+        ((a, (self.covariant)(b)), o)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Filter<First, Predicate> {
+    first: First,
+    predicate: Predicate,
+}
+
+impl<First, Predicate> fmt::Debug for Filter<First, Predicate>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Filter")
+            .field("first", &self.first)
+            .field("predicate", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First, Predicate> IndexedState<Input, Output, Option<Value>>
+    for Filter<First, Predicate>
+where
+    First: IndexedState<Input, Output, Value>,
+    Predicate: FnOnce(&Value) -> bool,
+{
+    fn run(self, state: Input) -> (Option<Value>, Output) {
+        let (a, o) = self.first.run(state);
+        let a = if (self.predicate)(&a) { Some(a) } else { None };
+        (a, o)
+    }
+}
+
+pub struct IfElse<First, OnTrue, OnFalse, Phantom> {
+    first: First,
+    on_true: OnTrue,
+    on_false: OnFalse,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, OnTrue, OnFalse, Phantom> fmt::Debug for IfElse<First, OnTrue, OnFalse, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IfElse")
+            .field("first", &self.first)
+            .field("on_true", &Closure)
+            .field("on_false", &Closure)
+            .finish()
+    }
+}
+
+impl<First, OnTrue, OnFalse, Phantom> Clone for IfElse<First, OnTrue, OnFalse, Phantom>
+where
+    First: Clone,
+    OnTrue: Clone,
+    OnFalse: Clone,
+{
+    fn clone(&self) -> Self {
+        IfElse {
+            first: self.first.clone(),
+            on_true: self.on_true.clone(),
+            on_false: self.on_false.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, OnTrue, OnFalse, Phantom> Copy for IfElse<First, OnTrue, OnFalse, Phantom>
+where
+    First: Copy,
+    OnTrue: Copy,
+    OnFalse: Copy,
+{
+}
+
+impl<Input, Output, SecondOutput, SecondValue, First, OnTrue, OnFalse, Second, Third>
+    IndexedState<Input, SecondOutput, SecondValue> for IfElse<First, OnTrue, OnFalse, Output>
+where
+    First: IndexedState<Input, Output, bool>,
+    OnTrue: FnOnce() -> Second,
+    OnFalse: FnOnce() -> Third,
+    Second: IndexedState<Output, SecondOutput, SecondValue>,
+    Third: IndexedState<Output, SecondOutput, SecondValue>,
+{
+    fn run(self, state: Input) -> (SecondValue, SecondOutput) {
+        let (a, o) = self.first.run(state);
+        let second = if a {
+            Either::Left((self.on_true)())
+        } else {
+            Either::Right((self.on_false)())
+        };
+        second.run(o)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Ensure<First, Predicate, Error> {
+    first: First,
+    predicate: Predicate,
+    error: Error,
+}
+
+impl<First, Predicate, Error> fmt::Debug for Ensure<First, Predicate, Error>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ensure")
+            .field("first", &self.first)
+            .field("predicate", &Closure)
+            .field("error", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, FirstError, First, Predicate, Error>
+    IndexedState<Input, Output, Result<Value, FirstError>> for Ensure<First, Predicate, Error>
+where
+    First: IndexedState<Input, Output, Value>,
+    Predicate: FnOnce(&Value) -> bool,
+    Error: FnOnce(&Value) -> FirstError,
+{
+    fn run(self, state: Input) -> (Result<Value, FirstError>, Output) {
+        let (a, o) = self.first.run(state);
+        let a = if (self.predicate)(&a) {
+            Ok(a)
+        } else {
+            Err((self.error)(&a))
+        };
+        (a, o)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct EnsureState<First, Predicate, Error> {
+    first: First,
+    predicate: Predicate,
+    error: Error,
+}
+
+impl<First, Predicate, Error> fmt::Debug for EnsureState<First, Predicate, Error>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnsureState")
+            .field("first", &self.first)
+            .field("predicate", &Closure)
+            .field("error", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, FirstError, First, Predicate, Error>
+    IndexedState<Input, Output, Result<Value, FirstError>> for EnsureState<First, Predicate, Error>
+where
+    First: IndexedState<Input, Output, Value>,
+    Predicate: FnOnce(&Output) -> bool,
+    Error: FnOnce(&Output) -> FirstError,
+{
+    fn run(self, state: Input) -> (Result<Value, FirstError>, Output) {
+        let (a, o) = self.first.run(state);
+        let a = if (self.predicate)(&o) {
+            Ok(a)
+        } else {
+            Err((self.error)(&o))
+        };
+        (a, o)
+    }
+}
+
+pub struct Reset<First, Phantom> {
+    first: First,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Phantom> fmt::Debug for Reset<First, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reset").field("first", &self.first).finish()
+    }
+}
+
+impl<First, Phantom> Clone for Reset<First, Phantom>
+where
+    First: Clone,
+{
+    fn clone(&self) -> Self {
+        Reset {
+            first: self.first.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Phantom> Copy for Reset<First, Phantom> where First: Copy {}
+
+impl<Input, Output, Value, First> IndexedState<Input, Input, Value> for Reset<First, Output>
+where
+    First: IndexedState<Input, Output, Value>,
+    Input: Clone,
+{
+    fn run(self, state: Input) -> (Value, Input) {
+        let (a, _) = self.first.run(state.clone());
+        (a, state)
+    }
+}
+
+pub struct Local<First, Enter, Phantom> {
+    first: First,
+    enter: Enter,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Enter, Phantom> fmt::Debug for Local<First, Enter, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Local")
+            .field("first", &self.first)
+            .field("enter", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Enter, Phantom> Clone for Local<First, Enter, Phantom>
+where
+    First: Clone,
+    Enter: Clone,
+{
+    fn clone(&self) -> Self {
+        Local {
+            first: self.first.clone(),
+            enter: self.enter.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Enter, Phantom> Copy for Local<First, Enter, Phantom>
+where
+    First: Copy,
+    Enter: Copy,
+{
+}
+
+impl<Input, Output, Value, First, Enter> IndexedState<Input, Input, Value>
+    for Local<First, Enter, Output>
+where
+    First: IndexedState<Input, Output, Value>,
+    Enter: FnOnce(&Input) -> Input,
+{
+    fn run(self, state: Input) -> (Value, Input) {
+        let (a, _) = self.first.run((self.enter)(&state));
+        (a, state)
+    }
+}
+
+pub struct Bracket<Acquire, Body, Release, Phantom> {
+    acquire: Acquire,
+    body: Body,
+    release: Release,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<Acquire, Body, Release, Phantom> fmt::Debug for Bracket<Acquire, Body, Release, Phantom>
+where
+    Acquire: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bracket")
+            .field("acquire", &self.acquire)
+            .field("body", &Closure)
+            .field("release", &Closure)
+            .finish()
+    }
+}
+
+impl<Acquire, Body, Release, Phantom> Clone for Bracket<Acquire, Body, Release, Phantom>
+where
+    Acquire: Clone,
+    Body: Clone,
+    Release: Clone,
+{
+    fn clone(&self) -> Self {
+        Bracket {
+            acquire: self.acquire.clone(),
+            body: self.body.clone(),
+            release: self.release.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Acquire, Body, Release, Phantom> Copy for Bracket<Acquire, Body, Release, Phantom>
+where
+    Acquire: Copy,
+    Body: Copy,
+    Release: Copy,
+{
+}
+
+impl<
+        Input,
+        FirstOutput,
+        SecondOutput,
+        ThirdOutput,
+        Resource,
+        Value,
+        ReleaseValue,
+        Acquire,
+        Body,
+        Release,
+        Second,
+        Third,
+    > IndexedState<Input, ThirdOutput, Value>
+    for Bracket<Acquire, Body, Release, (Resource, FirstOutput, SecondOutput, ReleaseValue)>
+where
+    Acquire: IndexedState<Input, FirstOutput, Resource>,
+    Body: FnOnce(&Resource) -> Second,
+    Second: IndexedState<FirstOutput, SecondOutput, Value>,
+    Release: FnOnce(Resource) -> Third,
+    Third: IndexedState<SecondOutput, ThirdOutput, ReleaseValue>,
+{
+    fn run(self, state: Input) -> (Value, ThirdOutput) {
+        let (resource, o) = self.acquire.run(state);
+        let (a, p) = (self.body)(&resource).run(o);
+        let (_, q) = (self.release)(resource).run(p);
+        (a, q)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Dbg<First, Writer> {
+    first: First,
+    writer: Writer,
+    label: &'static str,
+    location: &'static Location<'static>,
+}
+
+impl<First, Writer> fmt::Debug for Dbg<First, Writer>
+where
+    First: fmt::Debug,
+    Writer: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dbg")
+            .field("first", &self.first)
+            .field("writer", &self.writer)
+            .field("label", &self.label)
+            .field("location", &self.location)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First, Writer> IndexedState<Input, Output, Value> for Dbg<First, Writer>
+where
+    First: IndexedState<Input, Output, Value>,
+    Writer: fmt::Write,
+    Value: fmt::Debug,
+    Output: fmt::Debug,
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        let (a, o) = self.first.run(state);
+        let mut writer = self.writer;
+        let _ = writeln!(
+            writer,
+            "[{}:{}:{}] {}: value = {:#?}, state = {:#?}",
+            self.location.file(),
+            self.location.line(),
+            self.location.column(),
+            self.label,
+            a,
+            o
+        );
+        (a, o)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct DbgState<First, Writer> {
+    first: First,
+    writer: Writer,
+    label: &'static str,
+    location: &'static Location<'static>,
+}
+
+impl<First, Writer> fmt::Debug for DbgState<First, Writer>
+where
+    First: fmt::Debug,
+    Writer: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DbgState")
+            .field("first", &self.first)
+            .field("writer", &self.writer)
+            .field("label", &self.label)
+            .field("location", &self.location)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First, Writer> IndexedState<Input, Output, Value>
+    for DbgState<First, Writer>
+where
+    First: IndexedState<Input, Output, Value>,
+    Writer: fmt::Write,
+    Output: fmt::Debug,
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        let (a, o) = self.first.run(state);
+        let mut writer = self.writer;
+        let _ = writeln!(
+            writer,
+            "[{}:{}:{}] {}: state = {:#?}",
+            self.location.file(),
+            self.location.line(),
+            self.location.column(),
+            self.label,
+            o
+        );
+        (a, o)
+    }
+}
+
+/// Writes to standard error, used by `dbg` and `dbg_state`.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+pub struct Stderr;
+
+#[cfg(feature = "std")]
+impl fmt::Write for Stderr {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        eprint!("{}", s);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Labelled<First> {
+    first: First,
+    label: &'static str,
+}
+
+impl<First> fmt::Debug for Labelled<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Labelled")
+            .field("first", &self.first)
+            .field("label", &self.label)
+            .finish()
+    }
+}
+
+impl<First> Labelled<First> {
+    /// Returns the label attached by `named`.
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+}
+
+impl<Input, Output, Value, First> IndexedState<Input, Output, Value> for Labelled<First>
+where
+    First: IndexedState<Input, Output, Value>,
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        #[cfg(feature = "std")]
+        observe::notify(self.label);
+        self.first.run(state)
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Clone, Copy)]
+pub struct Timed<First, Clock> {
+    first: First,
+    clock: Clock,
+}
+
+#[cfg(feature = "std")]
+impl<First, Clock> fmt::Debug for Timed<First, Clock>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timed")
+            .field("first", &self.first)
+            .field("clock", &Closure)
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Input, Output, Value, First, Clock> IndexedState<Input, Output, (Value, Duration)>
+    for Timed<First, Clock>
+where
+    First: IndexedState<Input, Output, Value>,
+    Clock: FnMut() -> Instant,
+{
+    fn run(mut self, state: Input) -> ((Value, Duration), Output) {
+        let start = (self.clock)();
+        let (a, o) = self.first.run(state);
+        let elapsed = (self.clock)().duration_since(start);
+        ((a, elapsed), o)
+    }
+}
+
+pub struct RepeatFold<Factory, Accumulator, Fold, Phantom> {
+    factory: Factory,
+    count: usize,
+    init: Accumulator,
+    fold: Fold,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<Factory, Accumulator, Fold, Phantom> fmt::Debug
+    for RepeatFold<Factory, Accumulator, Fold, Phantom>
+where
+    Accumulator: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RepeatFold")
+            .field("factory", &Closure)
+            .field("count", &self.count)
+            .field("init", &self.init)
+            .field("fold", &Closure)
+            .finish()
+    }
+}
+
+impl<Factory, Accumulator, Fold, Phantom> Clone for RepeatFold<Factory, Accumulator, Fold, Phantom>
+where
+    Factory: Clone,
+    Accumulator: Clone,
+    Fold: Clone,
+{
+    fn clone(&self) -> Self {
+        RepeatFold {
+            factory: self.factory.clone(),
+            count: self.count,
+            init: self.init.clone(),
+            fold: self.fold.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Factory, Accumulator, Fold, Phantom> Copy for RepeatFold<Factory, Accumulator, Fold, Phantom>
+where
+    Factory: Copy,
+    Accumulator: Copy,
+    Fold: Copy,
+{
+}
+
+impl<State, Value, Accumulator, Factory, Step, Fold> IndexedState<State, State, Accumulator>
+    for RepeatFold<Factory, Accumulator, Fold, Value>
+where
+    Factory: FnMut(usize) -> Step,
+    Step: IndexedState<State, State, Value>,
+    Fold: FnMut(Accumulator, Value) -> Accumulator,
+{
+    fn run(mut self, mut state: State) -> (Accumulator, State) {
+        let mut accumulator = self.init;
+        for index in 0..self.count {
+            let (a, o) = (self.factory)(index).run(state);
+            accumulator = (self.fold)(accumulator, a);
+            state = o;
+        }
+        (accumulator, state)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ForEachItem<Items, Step> {
+    items: Items,
+    step: Step,
+}
+
+impl<Items, Step> fmt::Debug for ForEachItem<Items, Step>
+where
+    Items: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ForEachItem")
+            .field("items", &self.items)
+            .field("step", &Closure)
+            .finish()
+    }
+}
+
+impl<State, Items, Step, Second> IndexedState<State, State, ()> for ForEachItem<Items, Step>
+where
+    Items: IntoIterator,
+    Step: FnMut(Items::Item) -> Second,
+    Second: IndexedState<State, State, ()>,
+{
+    fn run(mut self, state: State) -> ((), State) {
+        let state = self
+            .items
+            .into_iter()
+            .fold(state, |state, item| (self.step)(item).execute(state));
+        ((), state)
+    }
+}
+
+/// The value of a loop with an iteration limit, which tells whether the loop finished
+/// or was stopped by the limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bounded<Value> {
+    Finished(Value),
+    Exceeded(Value),
+}
+
+impl<Value> Bounded<Value> {
+    /// Returns the value, regardless of whether the loop finished.
+    pub fn into_inner(self) -> Value {
+        match self {
+            Bounded::Finished(value) | Bounded::Exceeded(value) => value,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct LoopWhile<Factory, Predicate> {
+    factory: Factory,
+    predicate: Predicate,
+    limit: usize,
+}
+
+impl<Factory, Predicate> fmt::Debug for LoopWhile<Factory, Predicate> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoopWhile")
+            .field("factory", &Closure)
+            .field("predicate", &Closure)
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+impl<State, Value, Factory, Step, Predicate> IndexedState<State, State, Bounded<Value>>
+    for LoopWhile<Factory, Predicate>
+where
+    Factory: FnMut() -> Step,
+    Step: IndexedState<State, State, Value>,
+    Predicate: FnMut(&Value) -> bool,
+{
+    fn run(mut self, state: State) -> (Bounded<Value>, State) {
+        let (mut a, mut o) = (self.factory)().run(state);
+        let mut count = 1;
+        while (self.predicate)(&a) {
+            if count >= self.limit {
+                return (Bounded::Exceeded(a), o);
+            }
+            (a, o) = (self.factory)().run(o);
+            count += 1;
+        }
+        (Bounded::Finished(a), o)
+    }
+}
+
+pub struct RepeatUntil<Factory, Predicate, Phantom> {
+    factory: Factory,
+    predicate: Predicate,
+    limit: usize,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<Factory, Predicate, Phantom> fmt::Debug for RepeatUntil<Factory, Predicate, Phantom> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RepeatUntil")
+            .field("factory", &Closure)
+            .field("predicate", &Closure)
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+impl<Factory, Predicate, Phantom> Clone for RepeatUntil<Factory, Predicate, Phantom>
+where
+    Factory: Clone,
+    Predicate: Clone,
+{
+    fn clone(&self) -> Self {
+        RepeatUntil {
+            factory: self.factory.clone(),
+            predicate: self.predicate.clone(),
+            limit: self.limit,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Factory, Predicate, Phantom> Copy for RepeatUntil<Factory, Predicate, Phantom>
+where
+    Factory: Copy,
+    Predicate: Copy,
+{
+}
+
+impl<State, Value, Factory, Step, Predicate> IndexedState<State, State, Bounded<usize>>
+    for RepeatUntil<Factory, Predicate, Value>
+where
+    Factory: FnMut() -> Step,
+    Step: IndexedState<State, State, Value>,
+    Predicate: FnMut(&State) -> bool,
+{
+    fn run(mut self, mut state: State) -> (Bounded<usize>, State) {
+        let mut count = 0;
+        while !(self.predicate)(&state) {
+            if count >= self.limit {
+                return (Bounded::Exceeded(count), state);
+            }
+            state = (self.factory)().execute(state);
+            count += 1;
+        }
+        (Bounded::Finished(count), state)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Loop<Accumulator, Body> {
+    init: Accumulator,
+    body: Body,
+}
+
+impl<Accumulator, Body> fmt::Debug for Loop<Accumulator, Body>
+where
+    Accumulator: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Loop")
+            .field("init", &self.init)
+            .field("body", &Closure)
+            .finish()
+    }
+}
+
+impl<State, Accumulator, Done, Body, Step> IndexedState<State, State, Done>
+    for Loop<Accumulator, Body>
+where
+    Body: FnMut(Accumulator) -> Step,
+    Step: IndexedState<State, State, ControlFlow<Done, Accumulator>>,
+{
+    fn run(mut self, mut state: State) -> (Done, State) {
+        let mut accumulator = self.init;
+        loop {
+            match (self.body)(accumulator).run(state) {
+                (ControlFlow::Continue(next), o) => {
+                    accumulator = next;
+                    state = o;
+                }
+                (ControlFlow::Break(done), o) => return (done, o),
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ForEachChunked<Items, Step, OnChunk> {
+    items: Items,
+    chunk_size: usize,
+    step: Step,
+    on_chunk: OnChunk,
+}
+
+impl<Items, Step, OnChunk> fmt::Debug for ForEachChunked<Items, Step, OnChunk>
+where
+    Items: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ForEachChunked")
+            .field("items", &self.items)
+            .field("chunk_size", &self.chunk_size)
+            .field("step", &Closure)
+            .field("on_chunk", &Closure)
+            .finish()
+    }
+}
+
+impl<State, Items, Step, Second, OnChunk> IndexedState<State, State, ()>
+    for ForEachChunked<Items, Step, OnChunk>
+where
+    Items: IntoIterator,
+    Step: FnMut(Items::Item) -> Second,
+    Second: IndexedState<State, State, ()>,
+    OnChunk: FnMut(&State, usize),
+{
+    fn run(mut self, mut state: State) -> ((), State) {
+        let chunk_size = self.chunk_size.max(1);
+        let mut processed = 0;
+        for item in self.items {
+            state = (self.step)(item).execute(state);
+            processed += 1;
+            if processed % chunk_size == 0 {
+                (self.on_chunk)(&state, processed);
+            }
+        }
+        if processed % chunk_size != 0 {
+            (self.on_chunk)(&state, processed);
+        }
+        ((), state)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct DrainUntil<Items, Step> {
+    items: Items,
+    step: Step,
+}
+
+impl<Items, Step> fmt::Debug for DrainUntil<Items, Step>
+where
+    Items: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DrainUntil")
+            .field("items", &self.items)
+            .field("step", &Closure)
+            .finish()
+    }
+}
+
+impl<State, Done, Items, Step, Second> IndexedState<State, State, (Option<Done>, Items::IntoIter)>
+    for DrainUntil<Items, Step>
+where
+    Items: IntoIterator,
+    Step: FnMut(Items::Item) -> Second,
+    Second: IndexedState<State, State, ControlFlow<Done>>,
+{
+    fn run(mut self, mut state: State) -> ((Option<Done>, Items::IntoIter), State) {
+        let mut items = self.items.into_iter();
+        for item in items.by_ref() {
+            match (self.step)(item).run(state) {
+                (ControlFlow::Continue(()), o) => state = o,
+                (ControlFlow::Break(done), o) => return ((Some(done), items), o),
+            }
+        }
+        ((None, items), state)
+    }
+}
+
+/// Stands in for a function field when debugging a `Stateful` structure, as functions are not `Debug`.
+pub(crate) struct Closure;
+
+impl fmt::Debug for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<closure>")
+    }
+}
+
+/// One of two `Stateful` structures with the same state and value types, chosen at run time.
+pub(crate) enum Either<Left, Right> {
+    Left(Left),
+    Right(Right),
+}
+
+impl<Input, Output, Value, Left, Right> IndexedState<Input, Output, Value> for Either<Left, Right>
+where
+    Left: IndexedState<Input, Output, Value>,
+    Right: IndexedState<Input, Output, Value>,
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        match self {
+            Either::Left(left) => left.run(state),
+            Either::Right(right) => right.run(state),
+        }
+    }
+}
+
+/// Checks at compile time that combinator structures are `Send` and `Sync` when the structures and
+/// functions they hold are, regardless of the types that only appear in their phantom parameters.
+const _: () = {
+    const fn send_sync<T: Send + Sync>() {}
+
+    type Step = fn(u8) -> (u8, u8);
+    type Function = fn(u8) -> u8;
+    type Kleisli = fn(u8) -> Step;
+    type NotSend = *const u8;
+
+    send_sync::<State<Step>>();
+    send_sync::<New<NotSend>>();
+    send_sync::<Gets<Function>>();
+    send_sync::<Pure<NotSend, u8>>();
+    send_sync::<Map<Step, Function, NotSend>>();
+    send_sync::<MapState<Step, Function, NotSend>>();
+    send_sync::<AndThen<Step, Kleisli, NotSend>>();
+    send_sync::<ContramapState<Step, Function>>();
+    send_sync::<Apply<Step, Step, NotSend>>();
+    send_sync::<Zip<Step, Step, NotSend>>();
+    send_sync::<Then<Step, Step, NotSend>>();
+    send_sync::<Bimap<Step, Function, Function, NotSend>>();
+    send_sync::<Local<Step, Function, NotSend>>();
+    send_sync::<Labelled<Step>>();
+    send_sync::<Fallible<Step>>();
+    send_sync::<TryMap<Step, Function, NotSend>>();
+    send_sync::<TryAndThen<Step, Kleisli, NotSend>>();
+    send_sync::<Select<Step, Kleisli, Kleisli, NotSend>>();
+    send_sync::<RepeatFold<Function, u8, Function, NotSend>>();
+    send_sync::<Iterate<Step, u8, NotSend>>();
+    send_sync::<ScanState<core::ops::Range<u8>, Kleisli, u8, NotSend>>();
+    send_sync::<Driver<u8>>();
+    #[cfg(feature = "alloc")]
+    {
+        send_sync::<Sequence<Step>>();
+        send_sync::<History<Function, NotSend>>();
+        send_sync::<ArcShared<Step>>();
+    }
+    #[cfg(feature = "std")]
+    send_sync::<Timed<Step, fn() -> Instant>>();
+};
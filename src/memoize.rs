@@ -0,0 +1,77 @@
+use crate::{Closure, IndexedState, IndexedStateMut};
+use core::fmt;
+use core::hash::Hash;
+use std::collections::HashMap;
+
+/// Caches the results of the structures created by `factory`, keyed by the input state.
+///
+/// The factory is only called for input states that have not been run before.
+pub fn memoized<Input, Output, Value, Factory, First>(
+    factory: Factory,
+) -> Memoized<Factory, Input, Output, Value>
+where
+    Input: Hash + Eq + Clone,
+    Output: Clone,
+    Value: Clone,
+    Factory: FnMut() -> First,
+    First: IndexedState<Input, Output, Value>,
+{
+    Memoized {
+        factory,
+        cache: HashMap::new(),
+    }
+}
+
+/// A reusable `Stateful` structure that caches its results by input state, created by `memoized`.
+#[derive(Clone)]
+pub struct Memoized<Factory, Input, Output, Value> {
+    factory: Factory,
+    cache: HashMap<Input, (Value, Output)>,
+}
+
+impl<Factory, Input, Output, Value> fmt::Debug for Memoized<Factory, Input, Output, Value>
+where
+    Input: fmt::Debug,
+    Output: fmt::Debug,
+    Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Memoized")
+            .field("factory", &Closure)
+            .field("cache", &self.cache)
+            .finish()
+    }
+}
+
+impl<Factory, Input, Output, Value, First> IndexedState<Input, Output, Value>
+    for &mut Memoized<Factory, Input, Output, Value>
+where
+    Input: Hash + Eq + Clone,
+    Output: Clone,
+    Value: Clone,
+    Factory: FnMut() -> First,
+    First: IndexedState<Input, Output, Value>,
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        self.run_mut(state)
+    }
+}
+
+impl<Factory, Input, Output, Value, First> IndexedStateMut<Input, Output, Value>
+    for Memoized<Factory, Input, Output, Value>
+where
+    Input: Hash + Eq + Clone,
+    Output: Clone,
+    Value: Clone,
+    Factory: FnMut() -> First,
+    First: IndexedState<Input, Output, Value>,
+{
+    fn run_mut(&mut self, state: Input) -> (Value, Output) {
+        if let Some(cached) = self.cache.get(&state) {
+            return cached.clone();
+        }
+        let result = (self.factory)().run(state.clone());
+        self.cache.insert(state, result.clone());
+        result
+    }
+}
@@ -0,0 +1,55 @@
+use crate::IndexedState;
+use std::boxed::Box;
+use std::cell::RefCell;
+
+type Observer = Box<dyn FnMut(&'static str)>;
+
+std::thread_local! {
+    static OBSERVER: RefCell<Option<Observer>> = const { RefCell::new(None) };
+}
+
+/// Calls the observer registered by `run_with_observer` on this thread, if there is one.
+pub(crate) fn notify(label: &'static str) {
+    // The observer is taken out while it is called,
+    // so it may run observed structures itself without a double borrow.
+    if let Some(mut observer) = OBSERVER.with(|cell| cell.borrow_mut().take()) {
+        observer(label);
+        OBSERVER.with(|cell| {
+            let mut slot = cell.borrow_mut();
+            if slot.is_none() {
+                *slot = Some(observer);
+            }
+        });
+    }
+}
+
+/// Restores the previously registered observer, even if running the structure panics.
+struct Restore(Option<Observer>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        OBSERVER.with(|cell| *cell.borrow_mut() = previous);
+    }
+}
+
+/// Allows observing which labelled structures run, as created by `named`.
+pub trait Observable<Input, Output, Value>: IndexedState<Input, Output, Value> {
+    /// Like `run`, calling `observer` with the label of each structure created by `named`
+    /// as it starts running, in the order they run.
+    ///
+    /// The observer is registered for the current thread until this returns.
+    fn run_with_observer<Observer>(self, state: Input, observer: Observer) -> (Value, Output)
+    where
+        Observer: FnMut(&'static str) + 'static,
+    {
+        let previous = OBSERVER.with(|cell| cell.borrow_mut().replace(Box::new(observer)));
+        let _restore = Restore(previous);
+        self.run(state)
+    }
+}
+
+impl<Input, Output, Value, First> Observable<Input, Output, Value> for First where
+    First: IndexedState<Input, Output, Value>
+{
+}
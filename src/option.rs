@@ -0,0 +1,289 @@
+use crate::{Closure, IndexedState};
+use core::fmt;
+use core::marker::PhantomData;
+
+/// Combinators for `Stateful` structures where the value of `A` is an `Option`.
+pub trait OptionIndexedState<Input, Output, Inner>:
+    IndexedState<Input, Output, Option<Inner>>
+{
+    /// Returns the contained value of `A`, or `default` when it is `None`.
+    fn value_unwrap_or(self, default: Inner) -> ValueUnwrapOr<Self, Inner> {
+        ValueUnwrapOr {
+            first: self,
+            default,
+        }
+    }
+
+    /// Returns the contained value of `A`, or calls `default` when it is `None`.
+    fn value_unwrap_or_else<Thunk>(self, default: Thunk) -> ValueUnwrapOrElse<Self, Thunk>
+    where
+        Thunk: FnOnce() -> Inner,
+    {
+        ValueUnwrapOrElse {
+            first: self,
+            default,
+        }
+    }
+
+    /// Applies a covariant function to the contained value of `A`, or returns `default` when it is `None`.
+    fn value_map_or<Covariant, SecondValue>(
+        self,
+        default: SecondValue,
+        covariant: Covariant,
+    ) -> ValueMapOr<Self, SecondValue, Covariant, Inner>
+    where
+        Covariant: FnOnce(Inner) -> SecondValue,
+    {
+        ValueMapOr {
+            first: self,
+            default,
+            covariant,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Converts the value of `A` into a `Result`, using `error` when it is `None`.
+    fn ok_or<Error>(self, error: Error) -> OkOr<Self, Error> {
+        OkOr { first: self, error }
+    }
+
+    /// Converts the value of `A` into a `Result`, calling `error` when it is `None`.
+    fn ok_or_else<Thunk, Error>(self, error: Thunk) -> OkOrElse<Self, Thunk>
+    where
+        Thunk: FnOnce() -> Error,
+    {
+        OkOrElse { first: self, error }
+    }
+}
+
+impl<Input, Output, Inner, First> OptionIndexedState<Input, Output, Inner> for First where
+    First: IndexedState<Input, Output, Option<Inner>>
+{
+}
+
+/// Runs the `Stateful` structure when it is `Some`, returning its value as `Some`.
+/// When it is `None`, the state is returned untouched with `None` as the value.
+pub fn lift_option<State, Value, First>(first: Option<First>) -> LiftOption<First>
+where
+    First: IndexedState<State, State, Value>,
+{
+    LiftOption { first }
+}
+
+/// Runs the `Stateful` structure only when `condition` is `true`, like `lift_option`.
+pub fn run_if<State, Value, First>(condition: bool, first: First) -> LiftOption<First>
+where
+    First: IndexedState<State, State, Value>,
+{
+    lift_option(condition.then_some(first))
+}
+
+#[derive(Clone, Copy)]
+pub struct LiftOption<First> {
+    first: Option<First>,
+}
+
+impl<First> fmt::Debug for LiftOption<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LiftOption")
+            .field("first", &self.first)
+            .finish()
+    }
+}
+
+impl<State, Value, First> IndexedState<State, State, Option<Value>> for LiftOption<First>
+where
+    First: IndexedState<State, State, Value>,
+{
+    fn run(self, state: State) -> (Option<Value>, State) {
+        match self.first {
+            Some(first) => {
+                let (a, o) = first.run(state);
+                (Some(a), o)
+            }
+            None => (None, state),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ValueUnwrapOr<First, Inner> {
+    first: First,
+    default: Inner,
+}
+
+impl<First, Inner> fmt::Debug for ValueUnwrapOr<First, Inner>
+where
+    First: fmt::Debug,
+    Inner: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValueUnwrapOr")
+            .field("first", &self.first)
+            .field("default", &self.default)
+            .finish()
+    }
+}
+
+impl<Input, Output, Inner, First> IndexedState<Input, Output, Inner> for ValueUnwrapOr<First, Inner>
+where
+    First: IndexedState<Input, Output, Option<Inner>>,
+{
+    fn run(self, state: Input) -> (Inner, Output) {
+        let (a, o) = self.first.run(state);
+        (a.unwrap_or(self.default), o)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ValueUnwrapOrElse<First, Thunk> {
+    first: First,
+    default: Thunk,
+}
+
+impl<First, Thunk> fmt::Debug for ValueUnwrapOrElse<First, Thunk>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValueUnwrapOrElse")
+            .field("first", &self.first)
+            .field("default", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Inner, First, Thunk> IndexedState<Input, Output, Inner>
+    for ValueUnwrapOrElse<First, Thunk>
+where
+    First: IndexedState<Input, Output, Option<Inner>>,
+    Thunk: FnOnce() -> Inner,
+{
+    fn run(self, state: Input) -> (Inner, Output) {
+        let (a, o) = self.first.run(state);
+        (a.unwrap_or_else(self.default), o)
+    }
+}
+
+pub struct ValueMapOr<First, SecondValue, Covariant, Phantom> {
+    first: First,
+    default: SecondValue,
+    covariant: Covariant,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, SecondValue, Covariant, Phantom> fmt::Debug
+    for ValueMapOr<First, SecondValue, Covariant, Phantom>
+where
+    First: fmt::Debug,
+    SecondValue: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValueMapOr")
+            .field("first", &self.first)
+            .field("default", &self.default)
+            .field("covariant", &Closure)
+            .finish()
+    }
+}
+
+impl<First, SecondValue, Covariant, Phantom> Clone
+    for ValueMapOr<First, SecondValue, Covariant, Phantom>
+where
+    First: Clone,
+    SecondValue: Clone,
+    Covariant: Clone,
+{
+    fn clone(&self) -> Self {
+        ValueMapOr {
+            first: self.first.clone(),
+            default: self.default.clone(),
+            covariant: self.covariant.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, SecondValue, Covariant, Phantom> Copy
+    for ValueMapOr<First, SecondValue, Covariant, Phantom>
+where
+    First: Copy,
+    SecondValue: Copy,
+    Covariant: Copy,
+{
+}
+
+impl<Input, Output, Inner, SecondValue, First, Covariant> IndexedState<Input, Output, SecondValue>
+    for ValueMapOr<First, SecondValue, Covariant, Inner>
+where
+    First: IndexedState<Input, Output, Option<Inner>>,
+    Covariant: FnOnce(Inner) -> SecondValue,
+{
+    fn run(self, state: Input) -> (SecondValue, Output) {
+        let (a, o) = self.first.run(state);
+        (a.map_or(self.default, self.covariant), o)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct OkOr<First, Error> {
+    first: First,
+    error: Error,
+}
+
+impl<First, Error> fmt::Debug for OkOr<First, Error>
+where
+    First: fmt::Debug,
+    Error: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OkOr")
+            .field("first", &self.first)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<Input, Output, Inner, Error, First> IndexedState<Input, Output, Result<Inner, Error>>
+    for OkOr<First, Error>
+where
+    First: IndexedState<Input, Output, Option<Inner>>,
+{
+    fn run(self, state: Input) -> (Result<Inner, Error>, Output) {
+        let (a, o) = self.first.run(state);
+        (a.ok_or(self.error), o)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct OkOrElse<First, Thunk> {
+    first: First,
+    error: Thunk,
+}
+
+impl<First, Thunk> fmt::Debug for OkOrElse<First, Thunk>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OkOrElse")
+            .field("first", &self.first)
+            .field("error", &Closure)
+            .finish()
+    }
+}
+
+impl<Input, Output, Inner, Error, First, Thunk> IndexedState<Input, Output, Result<Inner, Error>>
+    for OkOrElse<First, Thunk>
+where
+    First: IndexedState<Input, Output, Option<Inner>>,
+    Thunk: FnOnce() -> Error,
+{
+    fn run(self, state: Input) -> (Result<Inner, Error>, Output) {
+        let (a, o) = self.first.run(state);
+        (a.ok_or_else(self.error), o)
+    }
+}
@@ -0,0 +1,71 @@
+use crate::{Closure, IndexedState};
+use core::fmt;
+use rayon::prelude::*;
+use std::vec::Vec;
+
+/// Applies the structure created by `kleisli` for every item of `items` in parallel,
+/// where the state is a `Vec` of independent sub-states and each item is paired with the sub-state
+/// at the same index.
+///
+/// Each structure only sees its own sub-state, which is what allows them to run in parallel.
+/// The sub-states and values are returned in the same order as `items`,
+/// matching `traverse` over the pairs of items and sub-states.
+///
+/// # Panics
+///
+/// Panics when `items` and the state have different lengths.
+pub fn par_traverse<SubState, Value, Item, Kleisli, Second>(
+    items: Vec<Item>,
+    kleisli: Kleisli,
+) -> ParTraverse<Item, Kleisli>
+where
+    SubState: Send,
+    Value: Send,
+    Item: Send,
+    Kleisli: Fn(Item) -> Second + Sync,
+    Second: IndexedState<SubState, SubState, Value>,
+{
+    ParTraverse { items, kleisli }
+}
+
+#[derive(Clone)]
+pub struct ParTraverse<Item, Kleisli> {
+    items: Vec<Item>,
+    kleisli: Kleisli,
+}
+
+impl<Item, Kleisli> fmt::Debug for ParTraverse<Item, Kleisli>
+where
+    Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParTraverse")
+            .field("items", &self.items)
+            .field("kleisli", &Closure)
+            .finish()
+    }
+}
+
+impl<SubState, Value, Item, Kleisli, Second> IndexedState<Vec<SubState>, Vec<SubState>, Vec<Value>>
+    for ParTraverse<Item, Kleisli>
+where
+    SubState: Send,
+    Value: Send,
+    Item: Send,
+    Kleisli: Fn(Item) -> Second + Sync,
+    Second: IndexedState<SubState, SubState, Value>,
+{
+    fn run(self, state: Vec<SubState>) -> (Vec<Value>, Vec<SubState>) {
+        assert_eq!(
+            self.items.len(),
+            state.len(),
+            "par_traverse requires one sub-state per item"
+        );
+        let kleisli = &self.kleisli;
+        self.items
+            .into_par_iter()
+            .zip(state)
+            .map(|(item, sub_state)| kleisli(item).run(sub_state))
+            .unzip()
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{Closure, IndexedState, IndexedStateMut, Then};
+use core::fmt;
+use core::marker::PhantomData;
+
+/// A reusable definition of a `Stateful` structure, holding the factory that creates it.
+///
+/// Each run creates a new structure from the factory, so the same pipeline can be stored,
+/// passed around and run any number of times.
+#[derive(Clone, Copy)]
+pub struct Pipeline<Factory> {
+    factory: Factory,
+}
+
+impl<Factory> fmt::Debug for Pipeline<Factory> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("factory", &Closure)
+            .finish()
+    }
+}
+
+impl<Factory> Pipeline<Factory> {
+    /// Creates a pipeline from the factory that creates its structure.
+    pub fn new<First>(factory: Factory) -> Self
+    where
+        Factory: FnMut() -> First,
+    {
+        Pipeline { factory }
+    }
+
+    /// Creates a new structure from the factory.
+    pub fn build<First>(&mut self) -> First
+    where
+        Factory: FnMut() -> First,
+    {
+        (self.factory)()
+    }
+
+    /// Creates a new structure from the factory and runs it with `state`.
+    pub fn run<Input, Output, Value, First>(&mut self, state: Input) -> (Value, Output)
+    where
+        Factory: FnMut() -> First,
+        First: IndexedState<Input, Output, Value>,
+    {
+        self.build().run(state)
+    }
+
+    /// Borrows this pipeline as a function that runs it with every state it is given.
+    pub fn runner<Input, Output, Value, First>(
+        &mut self,
+    ) -> impl FnMut(Input) -> (Value, Output) + '_
+    where
+        Factory: FnMut() -> First,
+        First: IndexedState<Input, Output, Value>,
+    {
+        move |state| self.run(state)
+    }
+
+    /// Creates a pipeline that runs the structure of this pipeline and then the structure of `second`,
+    /// like `then`.
+    pub fn then<Output, Value, First, SecondFactory, Second, SecondOutput, SecondValue>(
+        mut self,
+        mut second: Pipeline<SecondFactory>,
+    ) -> Pipeline<impl FnMut() -> Then<First, Second, (Value, Output)>>
+    where
+        Factory: FnMut() -> First,
+        SecondFactory: FnMut() -> Second,
+        Second: IndexedState<Output, SecondOutput, SecondValue>,
+    {
+        Pipeline {
+            factory: move || Then {
+                first: self.build(),
+                second: second.build(),
+                phantom: PhantomData,
+            },
+        }
+    }
+}
+
+impl<Input, Output, Value, Factory, First> IndexedStateMut<Input, Output, Value>
+    for Pipeline<Factory>
+where
+    Factory: FnMut() -> First,
+    First: IndexedState<Input, Output, Value>,
+{
+    fn run_mut(&mut self, state: Input) -> (Value, Output) {
+        self.run(state)
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{Closure, IndexedState};
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+/// Runs the structure created by `factory` `count` times, threading the state through each run
+/// and returning a clone of the state after every run as the value.
+///
+/// The state is cloned once per run, and a `count` of `0` returns the input state untouched.
+/// Use `accumulate` to collect the value of every run instead.
+pub fn history<State, Value, Factory, Step>(
+    factory: Factory,
+    count: usize,
+) -> History<Factory, Value>
+where
+    State: Clone,
+    Factory: FnMut() -> Step,
+    Step: IndexedState<State, State, Value>,
+{
+    History {
+        factory,
+        count,
+        phantom: PhantomData,
+    }
+}
+
+/// Runs the structure created by `factory` `count` times, threading the state through each run
+/// and collecting every value.
+///
+/// The factory is given the index of the run, and a `count` of `0` returns the input state untouched.
+pub fn repeat<State, Value, Factory, Step>(factory: Factory, count: usize) -> Repeat<Factory>
+where
+    Factory: FnMut(usize) -> Step,
+    Step: IndexedState<State, State, Value>,
+{
+    Repeat { factory, count }
+}
+
+/// Runs the structure created by `factory` `count` times like `repeat`, collecting the value of every run.
+///
+/// The factory is given the index of the run, so the steps can vary between runs.
+/// Use `history` to collect the state after every run instead.
+pub fn accumulate<State, Value, Factory, Step>(factory: Factory, count: usize) -> Repeat<Factory>
+where
+    Factory: FnMut(usize) -> Step,
+    Step: IndexedState<State, State, Value>,
+{
+    repeat(factory, count)
+}
+
+pub struct History<Factory, Phantom> {
+    factory: Factory,
+    count: usize,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<Factory, Phantom> fmt::Debug for History<Factory, Phantom> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("History")
+            .field("factory", &Closure)
+            .field("count", &self.count)
+            .finish()
+    }
+}
+
+impl<Factory, Phantom> Clone for History<Factory, Phantom>
+where
+    Factory: Clone,
+{
+    fn clone(&self) -> Self {
+        History {
+            factory: self.factory.clone(),
+            count: self.count,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Factory, Phantom> Copy for History<Factory, Phantom> where Factory: Copy {}
+
+impl<State, Value, Factory, Step> IndexedState<State, State, Vec<State>> for History<Factory, Value>
+where
+    State: Clone,
+    Factory: FnMut() -> Step,
+    Step: IndexedState<State, State, Value>,
+{
+    fn run(mut self, mut state: State) -> (Vec<State>, State) {
+        let mut states = Vec::with_capacity(self.count);
+        for _ in 0..self.count {
+            state = (self.factory)().execute(state);
+            states.push(state.clone());
+        }
+        (states, state)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Repeat<Factory> {
+    factory: Factory,
+    count: usize,
+}
+
+impl<Factory> fmt::Debug for Repeat<Factory> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Repeat")
+            .field("factory", &Closure)
+            .field("count", &self.count)
+            .finish()
+    }
+}
+
+impl<State, Value, Factory, Step> IndexedState<State, State, Vec<Value>> for Repeat<Factory>
+where
+    Factory: FnMut(usize) -> Step,
+    Step: IndexedState<State, State, Value>,
+{
+    fn run(mut self, mut state: State) -> (Vec<Value>, State) {
+        let mut values = Vec::with_capacity(self.count);
+        for index in 0..self.count {
+            let (a, o) = (self.factory)(index).run(state);
+            values.push(a);
+            state = o;
+        }
+        (values, state)
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{Closure, Either, IndexedState};
+use core::fmt;
+use core::marker::PhantomData;
+
+/// Combinators for `Stateful` structures where the value of `A` is a `Result`.
+pub trait ResultIndexedState<Input, Output, Inner, Error>:
+    IndexedState<Input, Output, Result<Inner, Error>>
+{
+    /// Continues with the structure returned by `on_ok` or `on_err`, depending on the value of `A`.
+    /// Only the function for the matching variant is called, and both structures must have the
+    /// same state and value types.
+    fn select<OnOk, OnErr, Second, Third, SecondOutput, SecondValue>(
+        self,
+        on_ok: OnOk,
+        on_err: OnErr,
+    ) -> Select<Self, OnOk, OnErr, (Inner, Error, Output)>
+    where
+        OnOk: FnOnce(Inner) -> Second,
+        OnErr: FnOnce(Error) -> Third,
+        Second: IndexedState<Output, SecondOutput, SecondValue>,
+        Third: IndexedState<Output, SecondOutput, SecondValue>,
+    {
+        Select {
+            first: self,
+            on_ok,
+            on_err,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Applies a function that goes from the `Ok` value of `A` to a new `Stateful` structure,
+    /// which is run with the output state of this structure.
+    ///
+    /// When the value is `Err`, the function is not called and the error is returned as the value
+    /// with the output state of this structure, so the next structure must keep the same state type.
+    fn bind_ok<Kleisli, Second, SecondInner>(self, kleisli: Kleisli) -> BindOk<Self, Kleisli, Inner>
+    where
+        Kleisli: FnOnce(Inner) -> Second,
+        Second: IndexedState<Output, Output, Result<SecondInner, Error>>,
+    {
+        BindOk {
+            stateful: self,
+            kleisli,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Consumes the input state and returns only the value of `A`.
+    fn try_evaluate(self, state: Input) -> Result<Inner, Error> {
+        self.evaluate(state)
+    }
+
+    /// Consumes the input state and returns the output state `O` when the value of `A` is `Ok`,
+    /// otherwise the error is returned and the output state is dropped.
+    fn try_execute(self, state: Input) -> Result<Output, Error> {
+        let (a, o) = self.run(state);
+        a.map(|_| o)
+    }
+}
+
+impl<Input, Output, Inner, Error, First> ResultIndexedState<Input, Output, Inner, Error> for First where
+    First: IndexedState<Input, Output, Result<Inner, Error>>
+{
+}
+
+pub struct Select<First, OnOk, OnErr, Phantom> {
+    first: First,
+    on_ok: OnOk,
+    on_err: OnErr,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, OnOk, OnErr, Phantom> fmt::Debug for Select<First, OnOk, OnErr, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Select")
+            .field("first", &self.first)
+            .field("on_ok", &Closure)
+            .field("on_err", &Closure)
+            .finish()
+    }
+}
+
+impl<First, OnOk, OnErr, Phantom> Clone for Select<First, OnOk, OnErr, Phantom>
+where
+    First: Clone,
+    OnOk: Clone,
+    OnErr: Clone,
+{
+    fn clone(&self) -> Self {
+        Select {
+            first: self.first.clone(),
+            on_ok: self.on_ok.clone(),
+            on_err: self.on_err.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, OnOk, OnErr, Phantom> Copy for Select<First, OnOk, OnErr, Phantom>
+where
+    First: Copy,
+    OnOk: Copy,
+    OnErr: Copy,
+{
+}
+
+impl<Input, Output, SecondOutput, Inner, Error, SecondValue, First, OnOk, OnErr, Second, Third>
+    IndexedState<Input, SecondOutput, SecondValue>
+    for Select<First, OnOk, OnErr, (Inner, Error, Output)>
+where
+    First: IndexedState<Input, Output, Result<Inner, Error>>,
+    OnOk: FnOnce(Inner) -> Second,
+    OnErr: FnOnce(Error) -> Third,
+    Second: IndexedState<Output, SecondOutput, SecondValue>,
+    Third: IndexedState<Output, SecondOutput, SecondValue>,
+{
+    fn run(self, state: Input) -> (SecondValue, SecondOutput) {
+        let (a, o) = self.first.run(state);
+        let second = match a {
+            Ok(inner) => Either::Left((self.on_ok)(inner)),
+            Err(error) => Either::Right((self.on_err)(error)),
+        };
+        second.run(o)
+    }
+}
+
+pub struct BindOk<First, Kleisli, Phantom> {
+    stateful: First,
+    kleisli: Kleisli,
+    phantom: PhantomData<fn() -> Phantom>,
+}
+
+impl<First, Kleisli, Phantom> fmt::Debug for BindOk<First, Kleisli, Phantom>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BindOk")
+            .field("stateful", &self.stateful)
+            .field("kleisli", &Closure)
+            .finish()
+    }
+}
+
+impl<First, Kleisli, Phantom> Clone for BindOk<First, Kleisli, Phantom>
+where
+    First: Clone,
+    Kleisli: Clone,
+{
+    fn clone(&self) -> Self {
+        BindOk {
+            stateful: self.stateful.clone(),
+            kleisli: self.kleisli.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<First, Kleisli, Phantom> Copy for BindOk<First, Kleisli, Phantom>
+where
+    First: Copy,
+    Kleisli: Copy,
+{
+}
+
+impl<Input, Output, Inner, SecondInner, Error, First, Kleisli, Second>
+    IndexedState<Input, Output, Result<SecondInner, Error>> for BindOk<First, Kleisli, Inner>
+where
+    First: IndexedState<Input, Output, Result<Inner, Error>>,
+    Kleisli: FnOnce(Inner) -> Second,
+    Second: IndexedState<Output, Output, Result<SecondInner, Error>>,
+{
+    fn run(self, state: Input) -> (Result<SecondInner, Error>, Output) {
+        match self.stateful.run(state) {
+            (Ok(inner), o) => (self.kleisli)(inner).run(o),
+            (Err(error), o) => (Err(error), o),
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use crate::{AndThen, ContramapState, IndexedState, Map, MapState};
+use core::marker::PhantomData;
+
+/// Allows the construction of computations that hold state and can be run more than once.
+/// Given an input state of `I`, returns the value of `A` and the output state `O`.
+///
+/// Every `FnMut(I) -> (A, O)` implements this trait,
+/// and the `*_mut` combinators mirror their `IndexedState` counterparts with `FnMut` functions,
+/// so the resulting structure implements both traits.
+pub trait IndexedStateMut<Input, Output, Value> {
+    /// Given an input state, returns an output state as `O` and the inner value as `A`,
+    /// leaving this structure available to be run again.
+    fn run_mut(&mut self, state: Input) -> (Value, Output);
+
+    /// Like `map`, where the function can be called once per run.
+    fn map_mut<Function, ValueNext>(self, closure: Function) -> Map<Self, Function, Value>
+    where
+        Self: Sized,
+        Function: FnMut(Value) -> ValueNext,
+    {
+        Map {
+            first: self,
+            covariant: closure,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Like `and_then`, where the function can be called once per run to create the next structure.
+    fn and_then_mut<Covariant, Second, SecondOutput, SecondValue>(
+        self,
+        kleisli: Covariant,
+    ) -> AndThen<Self, Covariant, (Value, Output)>
+    where
+        Self: Sized,
+        Second: IndexedState<Output, SecondOutput, SecondValue>,
+        Covariant: FnMut(Value) -> Second,
+    {
+        AndThen {
+            stateful: self,
+            kleisli,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Like `map_state`, where the function can be called once per run.
+    fn map_state_mut<Covariant, SecondState>(
+        self,
+        closure: Covariant,
+    ) -> MapState<Self, Covariant, Output>
+    where
+        Self: Sized,
+        Covariant: FnMut(Output) -> SecondState,
+    {
+        MapState {
+            first: self,
+            covariant: closure,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Like `contramap_state`, where the function can be called once per run.
+    fn contramap_state_mut<Covariant, FirstInput>(
+        self,
+        contravariant: Covariant,
+    ) -> ContramapState<Self, Covariant>
+    where
+        Self: Sized,
+        Covariant: FnMut(FirstInput) -> Input,
+    {
+        ContramapState {
+            first: self,
+            contravariant,
+        }
+    }
+
+    /// Borrows this structure as a one-shot `IndexedState`, so it can be given to any combinator
+    /// while remaining available to be run again.
+    fn by_mut(&mut self) -> ByMut<'_, Self> {
+        ByMut { first: self }
+    }
+}
+
+impl<Input, Output, Value, Function> IndexedStateMut<Input, Output, Value> for Function
+where
+    Function: FnMut(Input) -> (Value, Output),
+{
+    fn run_mut(&mut self, state: Input) -> (Value, Output) {
+        self(state)
+    }
+}
+
+impl<FirstInput, SecondInput, SecondValue, First, Covariant, FirstValue>
+    IndexedStateMut<FirstInput, SecondInput, SecondValue> for Map<First, Covariant, FirstValue>
+where
+    First: IndexedStateMut<FirstInput, SecondInput, FirstValue>,
+    Covariant: FnMut(FirstValue) -> SecondValue,
+{
+    fn run_mut(&mut self, state: FirstInput) -> (SecondValue, SecondInput) {
+        let (a, o) = self.first.run_mut(state);
+        let b = (self.covariant)(a);
+        (b, o)
+    }
+}
+
+impl<FirstInput, SecondInput, SecondOutput, First, Function, FirstValue>
+    IndexedStateMut<FirstInput, SecondOutput, FirstValue> for MapState<First, Function, SecondInput>
+where
+    First: IndexedStateMut<FirstInput, SecondInput, FirstValue>,
+    Function: FnMut(SecondInput) -> SecondOutput,
+{
+    fn run_mut(&mut self, state: FirstInput) -> (FirstValue, SecondOutput) {
+        let (a, o) = self.first.run_mut(state);
+        let p = (self.covariant)(o);
+        (a, p)
+    }
+}
+
+impl<FirstInput, SecondOutput, FirstValue, First, Kleisli, SecondInput, Second, SecondValue>
+    IndexedStateMut<FirstInput, SecondOutput, SecondValue>
+    for AndThen<First, Kleisli, (FirstValue, SecondInput)>
+where
+    First: IndexedStateMut<FirstInput, SecondInput, FirstValue>,
+    Second: IndexedState<SecondInput, SecondOutput, SecondValue>,
+    Kleisli: FnMut(FirstValue) -> Second,
+{
+    fn run_mut(&mut self, state: FirstInput) -> (SecondValue, SecondOutput) {
+        let (a, o) = self.stateful.run_mut(state);
+        (self.kleisli)(a).run(o)
+    }
+}
+
+impl<Input, Output, Value, PreviousInput, Second, Contravariant>
+    IndexedStateMut<PreviousInput, Output, Value> for ContramapState<Second, Contravariant>
+where
+    Second: IndexedStateMut<Input, Output, Value>,
+    Contravariant: FnMut(PreviousInput) -> Input,
+{
+    fn run_mut(&mut self, state: PreviousInput) -> (Value, Output) {
+        let state = (self.contravariant)(state);
+        self.first.run_mut(state)
+    }
+}
+
+/// A borrowed `IndexedStateMut` that is run once as an `IndexedState`, created by `by_mut`.
+#[derive(Debug)]
+pub struct ByMut<'a, First: ?Sized> {
+    first: &'a mut First,
+}
+
+impl<Input, Output, Value, First> IndexedState<Input, Output, Value> for ByMut<'_, First>
+where
+    First: IndexedStateMut<Input, Output, Value> + ?Sized,
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        self.first.run_mut(state)
+    }
+}
+
+/// Allows the construction of computations that hold state and can be run more than once through a shared reference,
+/// such as from several threads behind an `Arc`.
+/// Given an input state of `I`, returns the value of `A` and the output state `O`.
+///
+/// Every `Fn(I) -> (A, O)` implements this trait,
+/// and the `*_ref` combinators mirror their `IndexedState` counterparts with `Fn` functions,
+/// so the resulting structure implements all three traits.
+pub trait IndexedStateRef<Input, Output, Value> {
+    /// Given an input state, returns an output state as `O` and the inner value as `A`,
+    /// leaving this structure available to be run again.
+    fn run_ref(&self, state: Input) -> (Value, Output);
+
+    /// Like `map`, where the function can be called any number of times.
+    fn map_ref<Function, ValueNext>(self, closure: Function) -> Map<Self, Function, Value>
+    where
+        Self: Sized,
+        Function: Fn(Value) -> ValueNext,
+    {
+        Map {
+            first: self,
+            covariant: closure,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Like `and_then`, where the function can be called any number of times to create the next structure.
+    fn and_then_ref<Covariant, Second, SecondOutput, SecondValue>(
+        self,
+        kleisli: Covariant,
+    ) -> AndThen<Self, Covariant, (Value, Output)>
+    where
+        Self: Sized,
+        Second: IndexedState<Output, SecondOutput, SecondValue>,
+        Covariant: Fn(Value) -> Second,
+    {
+        AndThen {
+            stateful: self,
+            kleisli,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Like `map_state`, where the function can be called any number of times.
+    fn map_state_ref<Covariant, SecondState>(
+        self,
+        closure: Covariant,
+    ) -> MapState<Self, Covariant, Output>
+    where
+        Self: Sized,
+        Covariant: Fn(Output) -> SecondState,
+    {
+        MapState {
+            first: self,
+            covariant: closure,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Like `contramap_state`, where the function can be called any number of times.
+    fn contramap_state_ref<Covariant, FirstInput>(
+        self,
+        contravariant: Covariant,
+    ) -> ContramapState<Self, Covariant>
+    where
+        Self: Sized,
+        Covariant: Fn(FirstInput) -> Input,
+    {
+        ContramapState {
+            first: self,
+            contravariant,
+        }
+    }
+
+    /// Borrows this structure as a one-shot `IndexedState`, so it can be given to any combinator
+    /// while remaining available to be run again.
+    fn by_ref(&self) -> ByRef<'_, Self> {
+        ByRef { first: self }
+    }
+}
+
+impl<Input, Output, Value, Function> IndexedStateRef<Input, Output, Value> for Function
+where
+    Function: Fn(Input) -> (Value, Output),
+{
+    fn run_ref(&self, state: Input) -> (Value, Output) {
+        self(state)
+    }
+}
+
+impl<FirstInput, SecondInput, SecondValue, First, Covariant, FirstValue>
+    IndexedStateRef<FirstInput, SecondInput, SecondValue> for Map<First, Covariant, FirstValue>
+where
+    First: IndexedStateRef<FirstInput, SecondInput, FirstValue>,
+    Covariant: Fn(FirstValue) -> SecondValue,
+{
+    fn run_ref(&self, state: FirstInput) -> (SecondValue, SecondInput) {
+        let (a, o) = self.first.run_ref(state);
+        let b = (self.covariant)(a);
+        (b, o)
+    }
+}
+
+impl<FirstInput, SecondInput, SecondOutput, First, Function, FirstValue>
+    IndexedStateRef<FirstInput, SecondOutput, FirstValue> for MapState<First, Function, SecondInput>
+where
+    First: IndexedStateRef<FirstInput, SecondInput, FirstValue>,
+    Function: Fn(SecondInput) -> SecondOutput,
+{
+    fn run_ref(&self, state: FirstInput) -> (FirstValue, SecondOutput) {
+        let (a, o) = self.first.run_ref(state);
+        let p = (self.covariant)(o);
+        (a, p)
+    }
+}
+
+impl<FirstInput, SecondOutput, FirstValue, First, Kleisli, SecondInput, Second, SecondValue>
+    IndexedStateRef<FirstInput, SecondOutput, SecondValue>
+    for AndThen<First, Kleisli, (FirstValue, SecondInput)>
+where
+    First: IndexedStateRef<FirstInput, SecondInput, FirstValue>,
+    Second: IndexedState<SecondInput, SecondOutput, SecondValue>,
+    Kleisli: Fn(FirstValue) -> Second,
+{
+    fn run_ref(&self, state: FirstInput) -> (SecondValue, SecondOutput) {
+        let (a, o) = self.stateful.run_ref(state);
+        (self.kleisli)(a).run(o)
+    }
+}
+
+impl<Input, Output, Value, PreviousInput, Second, Contravariant>
+    IndexedStateRef<PreviousInput, Output, Value> for ContramapState<Second, Contravariant>
+where
+    Second: IndexedStateRef<Input, Output, Value>,
+    Contravariant: Fn(PreviousInput) -> Input,
+{
+    fn run_ref(&self, state: PreviousInput) -> (Value, Output) {
+        let state = (self.contravariant)(state);
+        self.first.run_ref(state)
+    }
+}
+
+/// A borrowed `IndexedStateRef` that is run once as an `IndexedState`, created by `by_ref`.
+#[derive(Debug)]
+pub struct ByRef<'a, First: ?Sized> {
+    first: &'a First,
+}
+
+impl<First: ?Sized> Clone for ByRef<'_, First> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<First: ?Sized> Copy for ByRef<'_, First> {}
+
+impl<Input, Output, Value, First> IndexedState<Input, Output, Value> for ByRef<'_, First>
+where
+    First: IndexedStateRef<Input, Output, Value> + ?Sized,
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        self.first.run_ref(state)
+    }
+}
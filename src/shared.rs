@@ -0,0 +1,101 @@
+use crate::{IndexedState, IndexedStateRef};
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use core::fmt;
+
+/// Shares `first` behind an `Rc`, so the same structure can be stored in several places
+/// and run any number of times without being consumed.
+pub fn shared<First>(first: First) -> Shared<First> {
+    Shared {
+        first: Rc::new(first),
+    }
+}
+
+/// Like `shared`, sharing `first` behind an `Arc` so it is `Send` and `Sync` when `first` is.
+pub fn arc_shared<First>(first: First) -> ArcShared<First> {
+    ArcShared {
+        first: Arc::new(first),
+    }
+}
+
+pub struct Shared<First> {
+    first: Rc<First>,
+}
+
+impl<First> Clone for Shared<First> {
+    fn clone(&self) -> Self {
+        Shared {
+            first: Rc::clone(&self.first),
+        }
+    }
+}
+
+impl<First> fmt::Debug for Shared<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Shared")
+            .field("first", &self.first)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First> IndexedState<Input, Output, Value> for Shared<First>
+where
+    First: IndexedStateRef<Input, Output, Value>,
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        self.first.run_ref(state)
+    }
+}
+
+impl<Input, Output, Value, First> IndexedStateRef<Input, Output, Value> for Shared<First>
+where
+    First: IndexedStateRef<Input, Output, Value>,
+{
+    fn run_ref(&self, state: Input) -> (Value, Output) {
+        self.first.run_ref(state)
+    }
+}
+
+pub struct ArcShared<First> {
+    first: Arc<First>,
+}
+
+impl<First> Clone for ArcShared<First> {
+    fn clone(&self) -> Self {
+        ArcShared {
+            first: Arc::clone(&self.first),
+        }
+    }
+}
+
+impl<First> fmt::Debug for ArcShared<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArcShared")
+            .field("first", &self.first)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First> IndexedState<Input, Output, Value> for ArcShared<First>
+where
+    First: IndexedStateRef<Input, Output, Value>,
+{
+    fn run(self, state: Input) -> (Value, Output) {
+        self.first.run_ref(state)
+    }
+}
+
+impl<Input, Output, Value, First> IndexedStateRef<Input, Output, Value> for ArcShared<First>
+where
+    First: IndexedStateRef<Input, Output, Value>,
+{
+    fn run_ref(&self, state: Input) -> (Value, Output) {
+        self.first.run_ref(state)
+    }
+}
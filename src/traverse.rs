@@ -0,0 +1,501 @@
+use crate::{Closure, IndexedState, TryIndexedState};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::ControlFlow;
+
+/// The error of the item that failed, along with its index in the collection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ItemError<Error> {
+    pub index: usize,
+    pub error: Error,
+}
+
+impl<Error> fmt::Display for ItemError<Error>
+where
+    Error: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "item {} failed: {}", self.index, self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Error> std::error::Error for ItemError<Error> where Error: std::error::Error {}
+
+/// Applies the structure created by `kleisli` for every item of `items` in order,
+/// threading the state through each of them and collecting their values in the same order.
+///
+/// An empty `items` returns an empty `Vec` with the input state untouched.
+pub fn traverse<State, Value, Items, Kleisli, Second>(
+    items: Items,
+    kleisli: Kleisli,
+) -> Traverse<Items, Kleisli>
+where
+    Items: IntoIterator,
+    Kleisli: FnMut(Items::Item) -> Second,
+    Second: IndexedState<State, State, Value>,
+{
+    Traverse { items, kleisli }
+}
+
+#[derive(Clone, Copy)]
+pub struct Traverse<Items, Kleisli> {
+    items: Items,
+    kleisli: Kleisli,
+}
+
+impl<Items, Kleisli> fmt::Debug for Traverse<Items, Kleisli>
+where
+    Items: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Traverse")
+            .field("items", &self.items)
+            .field("kleisli", &Closure)
+            .finish()
+    }
+}
+
+impl<State, Value, Items, Kleisli, Second> IndexedState<State, State, Vec<Value>>
+    for Traverse<Items, Kleisli>
+where
+    Items: IntoIterator,
+    Kleisli: FnMut(Items::Item) -> Second,
+    Second: IndexedState<State, State, Value>,
+{
+    fn run(mut self, mut state: State) -> (Vec<Value>, State) {
+        let items = self.items.into_iter();
+        let mut values = Vec::with_capacity(items.size_hint().0);
+        for item in items {
+            let (a, o) = (self.kleisli)(item).run(state);
+            values.push(a);
+            state = o;
+        }
+        (values, state)
+    }
+}
+
+/// Applies the structure created by `kleisli` for every entry of `map` in ascending order of the keys,
+/// threading the shared state through each of them and replacing every entry with its value.
+///
+/// A `BTreeMap` is required so the order the shared state is threaded in is reproducible.
+pub fn traverse_map<State, Key, Entity, Value, Kleisli, Second>(
+    map: BTreeMap<Key, Entity>,
+    kleisli: Kleisli,
+) -> TraverseMap<Key, Entity, Kleisli>
+where
+    Key: Ord,
+    Kleisli: FnMut(&Key, Entity) -> Second,
+    Second: IndexedState<State, State, Value>,
+{
+    TraverseMap { map, kleisli }
+}
+
+#[derive(Clone)]
+pub struct TraverseMap<Key, Entity, Kleisli> {
+    map: BTreeMap<Key, Entity>,
+    kleisli: Kleisli,
+}
+
+impl<Key, Entity, Kleisli> fmt::Debug for TraverseMap<Key, Entity, Kleisli>
+where
+    Key: fmt::Debug,
+    Entity: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraverseMap")
+            .field("map", &self.map)
+            .field("kleisli", &Closure)
+            .finish()
+    }
+}
+
+impl<State, Key, Entity, Value, Kleisli, Second> IndexedState<State, State, BTreeMap<Key, Value>>
+    for TraverseMap<Key, Entity, Kleisli>
+where
+    Key: Ord,
+    Kleisli: FnMut(&Key, Entity) -> Second,
+    Second: IndexedState<State, State, Value>,
+{
+    fn run(mut self, mut state: State) -> (BTreeMap<Key, Value>, State) {
+        let mut values = BTreeMap::new();
+        for (key, entity) in self.map {
+            let (a, o) = (self.kleisli)(&key, entity).run(state);
+            values.insert(key, a);
+            state = o;
+        }
+        (values, state)
+    }
+}
+
+/// Applies the structure created by `kleisli` for every item of `items` in order,
+/// threading the state through each of them and separating the `Ok` values from the errors.
+///
+/// Every item runs and the state changes of failing items are kept.
+/// Each error is returned alongside the index of its item.
+pub fn partition_results<State, Value, Error, Items, Kleisli, Second>(
+    items: Items,
+    kleisli: Kleisli,
+) -> PartitionResults<Items, Kleisli>
+where
+    Items: IntoIterator,
+    Kleisli: FnMut(Items::Item) -> Second,
+    Second: IndexedState<State, State, Result<Value, Error>>,
+{
+    PartitionResults { items, kleisli }
+}
+
+#[derive(Clone, Copy)]
+pub struct PartitionResults<Items, Kleisli> {
+    items: Items,
+    kleisli: Kleisli,
+}
+
+impl<Items, Kleisli> fmt::Debug for PartitionResults<Items, Kleisli>
+where
+    Items: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PartitionResults")
+            .field("items", &self.items)
+            .field("kleisli", &Closure)
+            .finish()
+    }
+}
+
+impl<State, Value, Error, Items, Kleisli, Second>
+    IndexedState<State, State, (Vec<Value>, Vec<(usize, Error)>)>
+    for PartitionResults<Items, Kleisli>
+where
+    Items: IntoIterator,
+    Kleisli: FnMut(Items::Item) -> Second,
+    Second: IndexedState<State, State, Result<Value, Error>>,
+{
+    fn run(mut self, mut state: State) -> ((Vec<Value>, Vec<(usize, Error)>), State) {
+        let mut values = Vec::new();
+        let mut errors = Vec::new();
+        for (index, item) in self.items.into_iter().enumerate() {
+            let (a, o) = (self.kleisli)(item).run(state);
+            match a {
+                Ok(value) => values.push(value),
+                Err(error) => errors.push((index, error)),
+            }
+            state = o;
+        }
+        ((values, errors), state)
+    }
+}
+
+/// Applies the structure created by `kleisli` for every pair of consecutive items of `items` in order,
+/// threading the state through each of them and collecting their values in the same order.
+///
+/// Every item except the first is cloned, as it is also the start of the next pair.
+/// An iterator of `n` items creates `n - 1` structures, so fewer than two items return an empty `Vec`
+/// with the input state untouched.
+pub fn pairwise<State, Value, Items, Kleisli, Second>(
+    items: Items,
+    kleisli: Kleisli,
+) -> Pairwise<Items, Kleisli>
+where
+    Items: IntoIterator,
+    Items::Item: Clone,
+    Kleisli: FnMut(Items::Item, Items::Item) -> Second,
+    Second: IndexedState<State, State, Value>,
+{
+    Pairwise { items, kleisli }
+}
+
+#[derive(Clone, Copy)]
+pub struct Pairwise<Items, Kleisli> {
+    items: Items,
+    kleisli: Kleisli,
+}
+
+impl<Items, Kleisli> fmt::Debug for Pairwise<Items, Kleisli>
+where
+    Items: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pairwise")
+            .field("items", &self.items)
+            .field("kleisli", &Closure)
+            .finish()
+    }
+}
+
+impl<State, Value, Items, Kleisli, Second> IndexedState<State, State, Vec<Value>>
+    for Pairwise<Items, Kleisli>
+where
+    Items: IntoIterator,
+    Items::Item: Clone,
+    Kleisli: FnMut(Items::Item, Items::Item) -> Second,
+    Second: IndexedState<State, State, Value>,
+{
+    fn run(mut self, mut state: State) -> (Vec<Value>, State) {
+        let mut items = self.items.into_iter();
+        let mut values = Vec::with_capacity(items.size_hint().0.saturating_sub(1));
+        let Some(mut previous) = items.next() else {
+            return (values, state);
+        };
+        for next in items {
+            let (a, o) = (self.kleisli)(previous, next.clone()).run(state);
+            values.push(a);
+            state = o;
+            previous = next;
+        }
+        (values, state)
+    }
+}
+
+/// Like `traverse`, stopping at the first structure that returns `ControlFlow::Break`
+/// without creating the structures for the items after it.
+///
+/// The value is the values collected before the break, alongside the `Break` value
+/// or `None` when every item was applied.
+pub fn traverse_control<State, Value, Break, Items, Kleisli, Second>(
+    items: Items,
+    kleisli: Kleisli,
+) -> TraverseControl<Items, Kleisli>
+where
+    Items: IntoIterator,
+    Kleisli: FnMut(Items::Item) -> Second,
+    Second: IndexedState<State, State, ControlFlow<Break, Value>>,
+{
+    TraverseControl { items, kleisli }
+}
+
+#[derive(Clone, Copy)]
+pub struct TraverseControl<Items, Kleisli> {
+    items: Items,
+    kleisli: Kleisli,
+}
+
+impl<Items, Kleisli> fmt::Debug for TraverseControl<Items, Kleisli>
+where
+    Items: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraverseControl")
+            .field("items", &self.items)
+            .field("kleisli", &Closure)
+            .finish()
+    }
+}
+
+impl<State, Value, Break, Items, Kleisli, Second>
+    IndexedState<State, State, (Vec<Value>, Option<Break>)> for TraverseControl<Items, Kleisli>
+where
+    Items: IntoIterator,
+    Kleisli: FnMut(Items::Item) -> Second,
+    Second: IndexedState<State, State, ControlFlow<Break, Value>>,
+{
+    fn run(mut self, mut state: State) -> ((Vec<Value>, Option<Break>), State) {
+        let mut values = Vec::new();
+        for item in self.items {
+            match (self.kleisli)(item).run(state) {
+                (ControlFlow::Continue(value), o) => {
+                    values.push(value);
+                    state = o;
+                }
+                (ControlFlow::Break(done), o) => return ((values, Some(done)), o),
+            }
+        }
+        ((values, None), state)
+    }
+}
+
+/// Runs each `Stateful` structure in order, threading the state through them
+/// and collecting their values in the same order.
+///
+/// An empty `items` returns an empty `Vec` with the input state untouched.
+pub fn sequence<State, Value, First>(items: Vec<First>) -> Sequence<First>
+where
+    First: IndexedState<State, State, Value>,
+{
+    Sequence { items }
+}
+
+#[derive(Clone)]
+pub struct Sequence<First> {
+    items: Vec<First>,
+}
+
+impl<First> fmt::Debug for Sequence<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sequence")
+            .field("items", &self.items)
+            .finish()
+    }
+}
+
+impl<State, Value, First> IndexedState<State, State, Vec<Value>> for Sequence<First>
+where
+    First: IndexedState<State, State, Value>,
+{
+    fn run(self, mut state: State) -> (Vec<Value>, State) {
+        let mut values = Vec::with_capacity(self.items.len());
+        for item in self.items {
+            let (a, o) = item.run(state);
+            values.push(a);
+            state = o;
+        }
+        (values, state)
+    }
+}
+
+/// Runs each fallible `Stateful` structure in order, threading the state through them
+/// and collecting their values.
+///
+/// Stops at the first structure that fails, returning its error and index
+/// without running the structures after it.
+pub fn try_sequence<State, Value, First>(items: Vec<First>) -> TrySequence<First>
+where
+    First: TryIndexedState<State, State, Value>,
+{
+    TrySequence { items }
+}
+
+#[derive(Clone)]
+pub struct TrySequence<First> {
+    items: Vec<First>,
+}
+
+impl<First> fmt::Debug for TrySequence<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrySequence")
+            .field("items", &self.items)
+            .finish()
+    }
+}
+
+impl<State, Value, First> TryIndexedState<State, State, Vec<Value>> for TrySequence<First>
+where
+    First: TryIndexedState<State, State, Value>,
+{
+    type Error = ItemError<First::Error>;
+
+    fn try_run(self, mut state: State) -> Result<(Vec<Value>, State), Self::Error> {
+        let mut values = Vec::with_capacity(self.items.len());
+        for (index, item) in self.items.into_iter().enumerate() {
+            let (value, next) = item
+                .try_run(state)
+                .map_err(|error| ItemError { index, error })?;
+            values.push(value);
+            state = next;
+        }
+        Ok((values, state))
+    }
+}
+
+/// Runs every fallible `Stateful` structure in order, collecting all of their errors
+/// instead of stopping at the first one.
+///
+/// The state is threaded through the structures that succeed. As a failing structure drops its state,
+/// the state is cloned before each structure runs and the next structure continues from that clone.
+pub fn validate_all<State, Value, First>(items: Vec<First>) -> ValidateAll<First>
+where
+    State: Clone,
+    First: TryIndexedState<State, State, Value>,
+{
+    ValidateAll { items }
+}
+
+#[derive(Clone)]
+pub struct ValidateAll<First> {
+    items: Vec<First>,
+}
+
+impl<First> fmt::Debug for ValidateAll<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValidateAll")
+            .field("items", &self.items)
+            .finish()
+    }
+}
+
+impl<State, Value, First> IndexedState<State, State, Result<Vec<Value>, Vec<First::Error>>>
+    for ValidateAll<First>
+where
+    State: Clone,
+    First: TryIndexedState<State, State, Value>,
+{
+    fn run(self, mut state: State) -> (Result<Vec<Value>, Vec<First::Error>>, State) {
+        let mut values = Vec::with_capacity(self.items.len());
+        let mut errors = Vec::new();
+        for item in self.items {
+            match item.try_run(state.clone()) {
+                Ok((value, next)) => {
+                    values.push(value);
+                    state = next;
+                }
+                Err(error) => errors.push(error),
+            }
+        }
+        let result = if errors.is_empty() {
+            Ok(values)
+        } else {
+            Err(errors)
+        };
+        (result, state)
+    }
+}
+
+/// Like `validate_all`, but every structure runs against a clone of the input state,
+/// and the input state is returned untouched so no state changes are kept.
+pub fn validate_all_isolated<State, Value, First>(items: Vec<First>) -> ValidateAllIsolated<First>
+where
+    State: Clone,
+    First: TryIndexedState<State, State, Value>,
+{
+    ValidateAllIsolated { items }
+}
+
+#[derive(Clone)]
+pub struct ValidateAllIsolated<First> {
+    items: Vec<First>,
+}
+
+impl<First> fmt::Debug for ValidateAllIsolated<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValidateAllIsolated")
+            .field("items", &self.items)
+            .finish()
+    }
+}
+
+impl<State, Value, First> IndexedState<State, State, Result<Vec<Value>, Vec<First::Error>>>
+    for ValidateAllIsolated<First>
+where
+    State: Clone,
+    First: TryIndexedState<State, State, Value>,
+{
+    fn run(self, state: State) -> (Result<Vec<Value>, Vec<First::Error>>, State) {
+        let mut values = Vec::with_capacity(self.items.len());
+        let mut errors = Vec::new();
+        for item in self.items {
+            match item.try_run(state.clone()) {
+                Ok((value, _)) => values.push(value),
+                Err(error) => errors.push(error),
+            }
+        }
+        let result = if errors.is_empty() {
+            Ok(values)
+        } else {
+            Err(errors)
+        };
+        (result, state)
+    }
+}
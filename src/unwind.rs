@@ -0,0 +1,99 @@
+use crate::{IndexedState, TryIndexedState};
+use core::any::Any;
+use core::fmt;
+use std::boxed::Box;
+use std::panic::{self, UnwindSafe};
+use std::string::{String, ToString};
+
+/// The error returned when a `Stateful` structure panics, created by `catch_panic`.
+///
+/// Holds the message of the panic and, when created by `catch_panic_with_input`,
+/// the original input state so the computation can be retried.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PanicError<Input = ()> {
+    pub message: String,
+    pub input: Input,
+}
+
+impl<Input> PanicError<Input> {
+    fn from_payload(payload: Box<dyn Any + Send>, input: Input) -> Self {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast_ref::<&'static str>() {
+                Some(message) => message.to_string(),
+                None => String::from("Box<dyn Any>"),
+            },
+        };
+        PanicError { message, input }
+    }
+}
+
+impl<Input> fmt::Display for PanicError<Input> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "panicked: {}", self.message)
+    }
+}
+
+impl<Input> std::error::Error for PanicError<Input> where Input: fmt::Debug {}
+
+#[derive(Clone, Copy)]
+pub struct CatchPanic<First> {
+    pub(crate) first: First,
+}
+
+impl<First> fmt::Debug for CatchPanic<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CatchPanic")
+            .field("first", &self.first)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First> TryIndexedState<Input, Output, Value> for CatchPanic<First>
+where
+    Input: UnwindSafe,
+    First: IndexedState<Input, Output, Value> + UnwindSafe,
+{
+    type Error = PanicError;
+
+    fn try_run(self, state: Input) -> Result<(Value, Output), PanicError> {
+        let first = self.first;
+        panic::catch_unwind(move || first.run(state))
+            .map_err(|payload| PanicError::from_payload(payload, ()))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct CatchPanicWithInput<First> {
+    pub(crate) first: First,
+}
+
+impl<First> fmt::Debug for CatchPanicWithInput<First>
+where
+    First: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CatchPanicWithInput")
+            .field("first", &self.first)
+            .finish()
+    }
+}
+
+impl<Input, Output, Value, First> TryIndexedState<Input, Output, Value>
+    for CatchPanicWithInput<First>
+where
+    Input: Clone + UnwindSafe,
+    First: IndexedState<Input, Output, Value> + UnwindSafe,
+{
+    type Error = PanicError<Input>;
+
+    fn try_run(self, state: Input) -> Result<(Value, Output), PanicError<Input>> {
+        let first = self.first;
+        let input = state.clone();
+        panic::catch_unwind(move || first.run(input))
+            .map_err(|payload| PanicError::from_payload(payload, state))
+    }
+}
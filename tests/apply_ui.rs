@@ -0,0 +1,4 @@
+#[test]
+fn apply_reports_misuse_at_the_call_site() {
+    trybuild::TestCases::new().compile_fail("tests/ui/apply_*.rs");
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "alloc")]
+
+use indexed_state::*;
+
+enum Op {
+    Add(u32),
+    Double,
+    Reset,
+}
+
+fn compile(op: Op) -> BoxIndexedState<'static, u32, u32, ()> {
+    match op {
+        Op::Add(amount) => modify(move |n: u32| n + amount).boxed(),
+        Op::Double => modify(|n: u32| n * 2).boxed(),
+        Op::Reset => put(0).boxed(),
+    }
+}
+
+#[test]
+fn compose_all_runs_steps_chosen_at_runtime() {
+    let steps = [Op::Add(3), Op::Double, Op::Reset, Op::Add(2)]
+        .into_iter()
+        .map(compile)
+        .collect();
+
+    assert_eq!(compose_all(steps).run(1), ((), 2));
+}
+
+#[test]
+fn boxed_send_steps_are_stored_and_sent() {
+    struct Program {
+        steps: Vec<BoxSendIndexedState<'static, u32, u32, ()>>,
+    }
+
+    let program = Program {
+        steps: vec![
+            modify(|n: u32| n + 1).boxed_send(),
+            modify(|n: u32| n * 10).boxed_send(),
+        ],
+    };
+    let handle = std::thread::spawn(move || compose_all(program.steps).run(1));
+
+    assert_eq!(handle.join().unwrap(), ((), 20));
+}
+
+fn pipeline(verbose: bool) -> BoxIndexedState<'static, u32, u32, String> {
+    if verbose {
+        gets(|n: u32| n + 1).map(|n| format!("next is {n}")).boxed()
+    } else {
+        pure("quiet".to_string())
+            .and_then(|label| modify(|n: u32| n * 2).map(move |()| label))
+            .boxed()
+    }
+}
+
+#[test]
+fn boxed_returns_different_shapes_from_an_if() {
+    assert_eq!(pipeline(true).run(1), ("next is 2".to_string(), 2));
+    assert_eq!(pipeline(false).run(1), ("quiet".to_string(), 2));
+}
+
+#[test]
+fn boxed_send_moves_a_pipeline_to_another_thread() {
+    let pipeline = gets(|n: u32| n * 3)
+        .and_then(|tripled| put(tripled + 1))
+        .boxed_send();
+
+    let handle = std::thread::spawn(move || pipeline.run(2));
+
+    assert_eq!(handle.join().unwrap(), ((), 7));
+}
@@ -0,0 +1,13 @@
+use indexed_state::*;
+
+#[test]
+fn clones_three_deep_composition() {
+    let names = ["a".to_string(), "b".to_string()];
+    let step = gets(|n: usize| n + 1)
+        .and_then(move |n| pure(names[n % names.len()].clone()))
+        .map_state(|n| n * 10);
+    let copy = step.clone();
+
+    assert_eq!(step.run(0), ("b".to_string(), 10));
+    assert_eq!(copy.run(1), ("a".to_string(), 20));
+}
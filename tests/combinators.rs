@@ -0,0 +1,531 @@
+use indexed_state::*;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[test]
+fn replace_overwrites_the_value() {
+    assert_eq!(new().replace("done").run(1), ("done", 1));
+}
+
+#[test]
+fn void_discards_the_value() {
+    assert_eq!(gets(|n: u8| n + 1).void().run(1), ((), 2));
+}
+
+#[test]
+fn zip_changes_the_state_type_on_both_legs() {
+    let first = itransition(|n: u8| (n, n.to_string()));
+    let second = itransition(|text: String| (text.len(), vec![text]));
+
+    assert_eq!(first.zip(second).run(42), ((42, 2), vec!["42".to_string()]));
+}
+
+#[test]
+fn zip_with_sums_two_counter_reads() {
+    let step = gets(|n: u32| n + 1).zip_with(gets(|n: u32| n + 1), |a, b| a + b);
+
+    assert_eq!(step.run(1), (5, 3));
+}
+
+#[test]
+fn zip_with_moves_captured_data() {
+    let suffix = String::from("!");
+    let step = pure(1).zip_with(pure(2), move |a, b| format!("{}{suffix}", a + b));
+
+    assert_eq!(step.run(()), ("3!".to_string(), ()));
+}
+
+#[test]
+fn zip3_orders_values_through_three_index_changes() {
+    let step = itransition(|n: u8| ('a', u16::from(n) + 1)).zip3(
+        itransition(|n: u16| ('b', u32::from(n) + 1)),
+        itransition(|n: u32| ('c', u64::from(n) + 1)),
+    );
+
+    assert_eq!(step.run(1), (('a', 'b', 'c'), 4u64));
+}
+
+#[test]
+fn zip_macro_flattens_higher_arities() {
+    let step = zip!(pure(1), pure(2), pure(3), pure(4));
+
+    assert_eq!(step.run(()), ((1, 2, 3, 4), ()));
+}
+
+#[test]
+fn then_keeps_only_the_last_value() {
+    let step = modify(|n: u32| n + 1)
+        .then(gets(|n: u32| n * 2))
+        .then(gots(|n: u32| n.to_string()));
+
+    assert_eq!(step.run(1), ("4".to_string(), 4));
+}
+
+#[test]
+fn zip_left_keeps_both_state_changes() {
+    let step = gets(|n: u32| n + 1).zip_left(gets(|n: u32| n * 10));
+
+    assert_eq!(step.run(1), (2, 20));
+}
+
+#[test]
+fn zip_right_keeps_both_state_changes() {
+    let step = modify(|n: u32| n + 1).zip_right(gots(|n: u32| n * 10));
+
+    assert_eq!(step.run(1), (20, 2));
+}
+
+#[cfg(feature = "alloc")]
+fn collatz_step() -> impl IndexedState<u32, u32, ()> {
+    gots(|n: u32| n.is_multiple_of(2))
+        .map(|even| {
+            if even {
+                modify(|n: u32| n / 2).boxed()
+            } else {
+                modify(|n: u32| 3 * n + 1).boxed()
+            }
+        })
+        .flatten()
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn flatten_runs_the_structure_chosen_by_the_value() {
+    assert_eq!(collatz_step().run(4), ((), 2));
+    assert_eq!(collatz_step().run(3), ((), 10));
+}
+
+#[cfg(feature = "alloc")]
+fn increment_or_reset() -> impl IndexedState<u32, u32, ()> {
+    modify(|n: u32| n + 1).and_then_with_state(|(), n: &u32| {
+        if *n > 5 {
+            put(0).boxed()
+        } else {
+            modify(|n: u32| n * 2).boxed()
+        }
+    })
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn and_then_with_state_branches_on_the_state() {
+    assert_eq!(increment_or_reset().run(1), ((), 4));
+    assert_eq!(increment_or_reset().run(5), ((), 0));
+}
+
+#[test]
+fn flat_map_state_keeps_the_first_value() {
+    let step = gets(|n: u32| n + 1).flat_map_state(|n| {
+        let clamp = *n > 3;
+        modify(move |n: u32| if clamp { 3 } else { n }).replace("ignored")
+    });
+
+    assert_eq!(step.run(1), (2, 2));
+    assert_eq!(step.run(5), (6, 3));
+}
+
+#[test]
+fn inspect_runs_once_without_changing_anything() {
+    let sink = RefCell::new(Vec::new());
+    let step = gets(|n: u32| n + 1).inspect(|n| sink.borrow_mut().push(format!("saw {n}")));
+
+    assert_eq!(step.run(1), (2, 2));
+    assert_eq!(*sink.borrow(), vec!["saw 2".to_string()]);
+}
+
+#[test]
+fn inspect_state_asserts_in_the_middle_of_a_chain() {
+    let step = modify(|n: u32| n + 1)
+        .inspect_state(|n| assert_eq!(*n, 2))
+        .then(modify(|n: u32| n * 10));
+
+    assert_eq!(step.run(1), ((), 20));
+}
+
+#[test]
+fn tap_mut_adjusts_the_value_in_place() {
+    let step = gots(|n: u32| vec![n]).tap_mut(|values| values.push(0));
+
+    assert_eq!(step.run(1), (vec![1, 0], 1));
+}
+
+#[test]
+fn bimap_sees_the_raw_output() {
+    let step = gets(|n: u32| n + 1).bimap(|v| v * 10, |s| s.to_string());
+
+    assert_eq!(step.run(1), (20, "2".to_string()));
+}
+
+#[test]
+fn map_with_state_reads_the_counter() {
+    let step = gots(|n: u32| n * 10).map_with_state(|v, state| format!("{v}@{state}"));
+
+    assert_eq!(step.run(3), ("30@3".to_string(), 3));
+}
+
+#[test]
+fn map_state_with_value_only_borrows_the_value() {
+    struct PanicsOnClone(u32);
+
+    impl Clone for PanicsOnClone {
+        fn clone(&self) -> Self {
+            panic!("the value was cloned")
+        }
+    }
+
+    let step = gots(|n: u32| PanicsOnClone(n)).map_state_with_value(|s, v| s + v.0);
+    let (value, state) = step.run(2);
+
+    assert_eq!(value.0, 2);
+    assert_eq!(state, 4);
+}
+
+#[test]
+fn with_state_pairs_the_value_with_the_state() {
+    let step = gets(|n: u32| n + 1).map(|n| n * 10).with_state();
+
+    assert_eq!(step.run(1), ((20, 2), 2));
+}
+
+#[test]
+fn with_input_clones_before_running() {
+    #[derive(Debug)]
+    struct Recorded {
+        n: u32,
+        log: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl Clone for Recorded {
+        fn clone(&self) -> Self {
+            self.log.borrow_mut().push("clone");
+            Recorded {
+                n: self.n,
+                log: Rc::clone(&self.log),
+            }
+        }
+    }
+
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let step = modify(|state: Recorded| {
+        state.log.borrow_mut().push("run");
+        Recorded {
+            n: state.n + 1,
+            ..state
+        }
+    })
+    .with_input();
+    let ((input, ()), output) = step.run(Recorded {
+        n: 1,
+        log: Rc::clone(&log),
+    });
+
+    assert_eq!((input.n, output.n), (1, 2));
+    assert_eq!(*log.borrow(), vec!["clone", "run"]);
+}
+
+#[test]
+fn swap_channels_continues_with_the_former_value() {
+    let step = pure::<u32, _>("start".to_string())
+        .swap_channels()
+        .and_then(|n| modify(move |s: String| format!("{s}:{n}")));
+
+    assert_eq!(step.run(7), ((), "start:7".to_string()));
+}
+
+#[test]
+fn map_into_widens_the_value() {
+    assert_eq!(gets(|n: u8| n + 1).map_into::<u32>().run(1), (2u32, 2u8));
+}
+
+#[test]
+fn state_into_converts_between_domain_states() {
+    struct Draft(u32);
+
+    #[derive(Debug, PartialEq)]
+    struct Published(u32);
+
+    impl From<Draft> for Published {
+        fn from(draft: Draft) -> Self {
+            Published(draft.0)
+        }
+    }
+
+    let step = modify(|n: u32| Draft(n + 1)).state_into::<Published>();
+
+    assert_eq!(step.execute(1), Published(2));
+}
+
+#[test]
+fn apply_runs_the_function_first() {
+    let function = state(|mut log: Vec<&'static str>| {
+        log.push("function");
+        (|n: u32| n + 1, log)
+    });
+    let value = state(|mut log: Vec<&'static str>| {
+        log.push("value");
+        (1u32, log)
+    });
+
+    assert_eq!(
+        function.apply(value).run(Vec::new()),
+        (2, vec!["function", "value"])
+    );
+}
+
+#[test]
+fn ap_flipped_runs_the_value_first() {
+    let value = state(|mut log: Vec<&'static str>| {
+        log.push("value");
+        (1u32, log)
+    });
+    let function = state(|mut log: Vec<&'static str>| {
+        log.push("function");
+        (|n: u32| n + 1, log)
+    });
+
+    assert_eq!(
+        value.ap_flipped(function).run(Vec::new()),
+        (2, vec!["value", "function"])
+    );
+}
+
+#[test]
+fn provide_queues_transitions() {
+    let mut queue: Vec<Box<dyn FnOnce() -> (u32, u32)>> = Vec::new();
+    for n in 1..=3u32 {
+        let provided = gets(move |s: u32| s * n).provide(10);
+        queue.push(Box::new(move || provided.run(())));
+    }
+
+    let results: Vec<_> = queue.into_iter().map(|thunk| thunk()).collect();
+
+    assert_eq!(results, vec![(10, 10), (20, 20), (30, 30)]);
+}
+
+#[test]
+fn runs_from_a_non_trivial_default() {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Counter {
+        step: u32,
+        total: u32,
+    }
+
+    impl Default for Counter {
+        fn default() -> Self {
+            Counter {
+                step: 5,
+                total: 100,
+            }
+        }
+    }
+
+    let add = || {
+        modify(|counter: Counter| Counter {
+            total: counter.total + counter.step,
+            ..counter
+        })
+    };
+
+    assert_eq!(add().execute_default().total, 105);
+    assert_eq!(gots(|counter: Counter| counter.step).evaluate_default(), 5);
+    assert_eq!(add().run_default().1, add().execute(Counter::default()));
+}
+
+#[test]
+fn map_first_moves_the_second_element_through() {
+    struct PanicsOnClone(&'static str);
+
+    impl Clone for PanicsOnClone {
+        fn clone(&self) -> Self {
+            panic!("the element was cloned")
+        }
+    }
+
+    let step = pure(1u32)
+        .zip(pure(PanicsOnClone("kept")))
+        .map_first(|n| n + 1);
+    let ((first, second), ()) = step.run(());
+
+    assert_eq!((first, second.0), (2, "kept"));
+}
+
+#[test]
+fn map_second_moves_the_first_element_through() {
+    let step = pure(String::from("kept"))
+        .zip(pure(1u32))
+        .map_second(|n| n * 10);
+
+    assert_eq!(step.run(()), (("kept".to_string(), 10), ()));
+}
+
+fn increment_below_three() -> impl IndexedState<u32, u32, ()> {
+    gots(|n: u32| n < 3).if_else(|| modify(|n: u32| n + 1), || put(0))
+}
+
+#[test]
+fn if_else_increments_or_resets() {
+    assert_eq!(increment_below_three().execute(1), 2);
+    assert_eq!(increment_below_three().execute(3), 0);
+}
+
+#[test]
+fn ensure_rejects_finalizing_without_a_name() {
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct Builder {
+        name: Option<&'static str>,
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum BuildError {
+        MissingName,
+    }
+
+    let finalize = || {
+        gots(|builder: Builder| builder).ensure(
+            |builder| builder.name.is_some(),
+            |_| BuildError::MissingName,
+        )
+    };
+    let named = modify(|_: Builder| Builder { name: Some("door") }).then(finalize());
+
+    assert_eq!(
+        named.evaluate(Builder::default()),
+        Ok(Builder { name: Some("door") })
+    );
+    assert_eq!(
+        finalize().evaluate(Builder::default()),
+        Err(BuildError::MissingName)
+    );
+}
+
+fn withdraw(amount: i64) -> impl IndexedState<i64, i64, Result<(), i64>> {
+    modify(move |balance: i64| balance - amount)
+        .ensure_state(|balance| *balance >= 0, |balance| *balance)
+}
+
+#[test]
+fn ensure_state_checks_the_balance() {
+    assert_eq!(withdraw(5).run(10), (Ok(()), 5));
+    assert_eq!(withdraw(15).run(10), (Err(-5), -5));
+}
+
+#[test]
+fn ensure_state_failure_does_not_abort_the_chain() {
+    let step = withdraw(15).then(modify(|balance: i64| balance + 20));
+
+    assert_eq!(step.run(10), ((), 15));
+}
+
+#[test]
+fn reset_discards_the_inner_state_changes() {
+    #[derive(Debug)]
+    struct Counted {
+        n: u32,
+        clones: Rc<Cell<usize>>,
+    }
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            self.clones.set(self.clones.get() + 1);
+            Counted {
+                n: self.n,
+                clones: Rc::clone(&self.clones),
+            }
+        }
+    }
+
+    let clones = Rc::new(Cell::new(0));
+    let step = modify(|counted: Counted| Counted {
+        n: counted.n + 1,
+        ..counted
+    })
+    .replace("ran")
+    .reset();
+    let (value, state) = step.run(Counted {
+        n: 1,
+        clones: Rc::clone(&clones),
+    });
+
+    assert_eq!((value, state.n), ("ran", 1));
+    assert_eq!(clones.get(), 1);
+}
+
+#[test]
+fn local_restores_the_input_state() {
+    let step = gots(|n: u32| n * 2).local(|n| n + 10);
+
+    assert_eq!(step.run(1), (22, 1));
+}
+
+#[test]
+fn bracket_runs_the_phases_in_order() {
+    let step = bracket(
+        state(|mut log: Vec<&'static str>| {
+            log.push("acquire");
+            (5u32, log)
+        }),
+        |resource: &u32| {
+            let resource = *resource;
+            state(move |mut log: Vec<&'static str>| {
+                log.push("use");
+                (resource * 2, log)
+            })
+        },
+        |_: u32| {
+            modify(|mut log: Vec<&'static str>| {
+                log.push("release");
+                log
+            })
+        },
+    );
+
+    assert_eq!(
+        step.run(Vec::new()),
+        (10, vec!["acquire", "use", "release"])
+    );
+}
+
+#[test]
+fn run_cloned_keeps_the_structure_usable() {
+    let suffix = String::from("!");
+    let step = gets(move |text: String| text + &suffix);
+
+    assert_eq!(step.run_cloned("a".to_string()).1, "a!");
+    assert_eq!(step.run("b".to_string()).1, "b!");
+}
+
+#[test]
+fn run_with_ref_keeps_the_state_usable() {
+    let initial = vec![1, 2];
+
+    let (len, state) = state(|mut items: Vec<u32>| {
+        items.push(3);
+        (items.len(), items)
+    })
+    .run_with_ref(&initial);
+
+    assert_eq!((len, state), (3, vec![1, 2, 3]));
+    assert_eq!(initial, vec![1, 2]);
+}
+
+#[test]
+fn borrowed_closure_composes_twice() {
+    let step = |n: u32| (n, n + 1);
+
+    assert_eq!((&step).map(|n| n * 10).run(1), (10, 2));
+    assert_eq!((&step).and_then(|n| put(n * 100)).run(2), ((), 200));
+}
+
+#[test]
+fn mutably_borrowed_closure_keeps_its_captures() {
+    let mut calls = 0;
+    let mut step = |n: u32| {
+        calls += 1;
+        (n, n + 1)
+    };
+
+    assert_eq!((&mut step).map(|n| n * 10).run(1), (10, 2));
+    assert_eq!((&mut step).run(2), (2, 3));
+    assert_eq!(calls, 2);
+}
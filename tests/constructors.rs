@@ -0,0 +1,177 @@
+use indexed_state::*;
+use std::cell::Cell;
+use std::cell::RefCell;
+
+#[test]
+fn state_stored_in_struct_field() {
+    struct Machine {
+        step: State<fn(u32) -> (u32, u32)>,
+    }
+
+    let machine = Machine {
+        step: state(|n: u32| (n, n + 1)),
+    };
+
+    assert_eq!(machine.step.run(1), (1, 2));
+    assert_eq!(machine.step.run(2), (2, 3));
+}
+
+#[test]
+fn constructors_are_clone_through_compositions() {
+    let label = String::from("n=");
+    let step = gets(|n: u32| n + 1)
+        .map(move |n| format!("{label}{n}"))
+        .map_state(|n| n + 1);
+    let copy = step.clone();
+
+    assert_eq!(step.run(1), ("n=2".to_string(), 3));
+    assert_eq!(copy.run(2), ("n=3".to_string(), 4));
+}
+
+#[test]
+fn constructors_can_be_iterated() {
+    let states: Vec<u32> = iterate(gets(|n: u32| n + 1), 0).take(3).collect();
+
+    assert_eq!(states, vec![1, 2, 3]);
+}
+
+#[test]
+fn constructors_can_be_run_cloned() {
+    let step = pure(1);
+
+    assert_eq!(step.run_cloned(0), (1, 0));
+    assert_eq!(step.run_cloned(5), (1, 5));
+}
+
+#[test]
+fn put_replaces_the_state() {
+    let step = new().and_then(|_| put(5u8));
+
+    let () = step.evaluate(1u8);
+    assert_eq!(step.execute(1u8), 5);
+    assert_eq!(put::<u8, _>("done").run(1), ((), "done"));
+}
+
+#[test]
+fn modify_keeps_the_state_type() {
+    assert_eq!(modify(|n: u32| n * 2).run(3), ((), 6));
+}
+
+#[test]
+fn modify_changes_the_state_type() {
+    struct Builder {
+        name: &'static str,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct FinishedBuilder {
+        name: String,
+    }
+
+    let step = modify(|builder: Builder| FinishedBuilder {
+        name: builder.name.to_string(),
+    });
+
+    assert_eq!(
+        step.execute(Builder { name: "door" }),
+        FinishedBuilder {
+            name: "door".to_string()
+        }
+    );
+}
+
+#[test]
+fn pure_returns_the_state_untouched() {
+    assert_eq!(pure("value").run(3), ("value", 3));
+}
+
+#[test]
+fn transitions_model_a_door() {
+    #[derive(Debug, PartialEq)]
+    struct Open;
+
+    #[derive(Debug, PartialEq)]
+    struct Closed;
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Opened,
+        Closed,
+    }
+
+    let open = itransition(|_: Closed| (Event::Opened, Open));
+    let close = itransition(|_: Open| (Event::Closed, Closed));
+
+    assert_eq!(
+        open.zip(close).run(Closed),
+        ((Event::Opened, Event::Closed), Closed)
+    );
+    assert_eq!(imodify(|_: Open| Closed).run(Open), ((), Closed));
+}
+
+#[test]
+fn defer_calls_the_thunk_when_run() {
+    let calls = Cell::new(0);
+    let step = defer(|| {
+        calls.set(calls.get() + 1);
+        gets(|n: u32| n + 1)
+    });
+
+    assert_eq!(calls.get(), 0);
+    assert_eq!(step.run(1), (2, 2));
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn gots_ref_does_not_clone_the_state() {
+    struct PanicsOnClone(u32);
+
+    impl Clone for PanicsOnClone {
+        fn clone(&self) -> Self {
+            panic!("the state was cloned")
+        }
+    }
+
+    let (value, state) = gots_ref(|state: &PanicsOnClone| state.0 * 2).run(PanicsOnClone(3));
+
+    assert_eq!(value, 6);
+    assert_eq!(state.0, 3);
+}
+
+#[test]
+fn exchange_returns_the_previous_state() {
+    assert_eq!(exchange(10u32).run(3u32), (3, 10));
+    assert_eq!(exchange("next").run(1u8), (1, "next"));
+}
+
+#[test]
+fn exchange_archives_the_previous_state() {
+    let history = RefCell::new(Vec::new());
+    let archive = &history;
+    let migrate = |next: u32| {
+        exchange(next).and_then(move |previous| {
+            archive.borrow_mut().push(previous);
+            pure(())
+        })
+    };
+
+    let state = migrate(2).execute(1);
+    let state = migrate(3).execute(state);
+
+    assert_eq!(state, 3);
+    assert_eq!(*history.borrow(), vec![1, 2]);
+}
+
+#[test]
+fn from_pair_terminates_a_chain() {
+    let step = gets(|n: u32| n + 1).and_then(|n| from_pair((n * 10, "done")));
+
+    assert_eq!(step.run(1), (20, "done"));
+}
+
+#[test]
+fn swap_reorders_the_state() {
+    let step = swap().and_then(|()| modify(|(count, name): (u32, &str)| (count + 1, name)));
+
+    assert_eq!(step.run(("name", 1)), ((), (2, "name")));
+}
@@ -0,0 +1,30 @@
+#![cfg(feature = "anyhow")]
+
+use indexed_state::*;
+use std::cell::Cell;
+
+#[test]
+fn context_renders_in_order() {
+    let step = (|_: u32| Err::<((), u32), _>(std::io::Error::other("disk full")))
+        .context("saving ledger")
+        .context("closing day");
+
+    let error = step.try_run(1).unwrap_err();
+
+    assert_eq!(
+        format!("{error:#}"),
+        "closing day: saving ledger: disk full"
+    );
+}
+
+#[test]
+fn with_context_is_lazy_on_success() {
+    let called = Cell::new(false);
+    let step = (|n: u32| Ok::<_, std::io::Error>((n, n))).with_context(|| {
+        called.set(true);
+        "unused"
+    });
+
+    assert_eq!(step.try_run(1).unwrap(), (1, 1));
+    assert!(!called.get());
+}
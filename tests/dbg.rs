@@ -0,0 +1,27 @@
+use indexed_state::*;
+
+#[test]
+fn dbg_to_writes_label_value_and_state() {
+    let mut output = String::new();
+    let line = line!() + 1;
+    let step = gets(|n: u32| n + 1).dbg_to(&mut output, "step");
+
+    assert_eq!(step.run(1), (2, 2));
+    assert!(output.starts_with(&format!("[{}:{}:", file!(), line)));
+    assert!(output.ends_with("] step: value = 2, state = 2\n"));
+}
+
+#[test]
+fn dbg_state_to_writes_only_the_state() {
+    struct Opaque;
+
+    let mut output = String::new();
+    let (value, state) = modify(|n: u32| n * 3)
+        .map(|_| Opaque)
+        .dbg_state_to(&mut output, "tripled")
+        .run(2);
+
+    assert!(matches!(value, Opaque));
+    assert_eq!(state, 6);
+    assert!(output.ends_with("] tripled: state = 6\n"));
+}
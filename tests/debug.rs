@@ -0,0 +1,47 @@
+use indexed_state::*;
+
+#[test]
+fn renders_nested_composition() {
+    let step = gets(|n: u32| n + 1)
+        .map(|n| n * 2)
+        .and_then(|n| pure(n).map_state(|s: u32| s + 1));
+
+    assert_eq!(
+        format!("{step:?}"),
+        "AndThen { stateful: Map { first: Gets { covariant: <closure> }, covariant: <closure> }, kleisli: <closure> }"
+    );
+}
+
+#[test]
+fn renders_values_captured_by_constructors() {
+    let step = pure::<u8, _>("done").zip(put::<u8, _>(3u8));
+
+    assert_eq!(
+        format!("{step:?}"),
+        "Zip { first: Pure { value: \"done\" }, second: Put { state: 3 } }"
+    );
+}
+
+#[test]
+fn renders_ensure_without_debug_error() {
+    struct Missing;
+
+    let step = gots(|n: u32| n).ensure(|n| *n > 0, |_| Missing);
+
+    assert_eq!(
+        format!("{step:?}"),
+        "Ensure { first: Gots { covariant: <closure> }, predicate: <closure>, error: <closure> }"
+    );
+}
+
+#[test]
+fn renders_thread_without_debug_kleisli() {
+    let thread = [1u32, 2, 3]
+        .into_iter()
+        .thread(0u32, |n| gets(move |total: u32| total + n));
+
+    assert_eq!(
+        format!("{thread:?}"),
+        "Thread { scan: ScanState { items: IntoIter([1, 2, 3]), kleisli: <closure>, state: Some(0) } }"
+    );
+}
@@ -0,0 +1,15 @@
+use indexed_state::*;
+
+#[test]
+fn driver_applies_transitions_in_place() {
+    let mut driver = Driver::new(1u32);
+
+    let () = driver.apply(modify(|n: u32| n + 4));
+    let doubled = driver.apply(state(|n: u32| (n * 2, n * 2)));
+    assert_eq!(doubled, 10);
+    assert_eq!(*driver.state(), 10);
+
+    let (previous, driver) = driver.apply_indexed(state(|n: u32| (n, n.to_string())));
+    assert_eq!(previous, 10);
+    assert_eq!(driver.into_state(), "10");
+}
@@ -0,0 +1,225 @@
+use indexed_state::*;
+use std::cell::Cell;
+use std::convert::Infallible;
+use std::num::ParseIntError;
+
+#[test]
+fn closures_returning_results_are_fallible() {
+    let step = |n: u32| if n > 0 { Ok((n, n - 1)) } else { Err("empty") };
+
+    assert_eq!(step.try_run(2), Ok((2, 1)));
+    assert_eq!(step.try_evaluate(0), Err("empty"));
+}
+
+#[test]
+fn infallible_never_fails() {
+    let result: Result<((), u32), Infallible> = modify(|n: u32| n + 1).infallible().try_run(1);
+
+    assert_eq!(result, Ok(((), 2)));
+}
+
+#[test]
+fn try_map_transforms_the_value() {
+    let step = (|n: u32| Ok::<_, &str>((n, n + 1))).try_map(|v| Ok::<_, &str>(v * 10));
+
+    assert_eq!(step.try_run(1), Ok((10, 2)));
+}
+
+#[test]
+fn try_map_fails_after_the_state_transition() {
+    let transitioned = Cell::new(false);
+    let step = (|n: u32| {
+        transitioned.set(true);
+        Ok::<_, &str>((n, n + 1))
+    })
+    .try_map(|_| Err::<u32, _>("mapped"));
+
+    assert_eq!(step.try_run(1), Err("mapped"));
+    assert!(transitioned.get());
+}
+
+fn three_steps(
+    fail_at: usize,
+    calls: &Cell<usize>,
+) -> impl TryIndexedState<u32, u32, u32, Error = usize> + '_ {
+    let step = move |position: usize| {
+        move |n: u32| -> Result<(u32, u32), usize> {
+            calls.set(calls.get() + 1);
+            if position == fail_at {
+                Err(position)
+            } else {
+                Ok((n + 1, n + 1))
+            }
+        }
+    };
+    step(1)
+        .try_and_then(move |_| step(2))
+        .try_and_then(move |_| step(3))
+}
+
+#[test]
+fn try_and_then_short_circuits_at_each_position() {
+    for fail_at in 1..=3 {
+        let calls = Cell::new(0);
+
+        assert_eq!(three_steps(fail_at, &calls).try_run(0), Err(fail_at));
+        assert_eq!(calls.get(), fail_at);
+    }
+}
+
+#[test]
+fn try_and_then_runs_every_step_on_success() {
+    let calls = Cell::new(0);
+
+    assert_eq!(three_steps(0, &calls).try_run(0), Ok((3, 3)));
+    assert_eq!(calls.get(), 3);
+}
+
+fn parse_state() -> impl TryIndexedState<String, u32, (), Error = ParseIntError> {
+    (|text: String| Ok::<_, ParseIntError>(((), text)))
+        .try_map_state(|text: String| text.parse::<u32>())
+}
+
+#[test]
+fn try_map_state_parses_the_state() {
+    assert_eq!(parse_state().try_run("42".to_string()), Ok(((), 42)));
+    assert!(parse_state().try_run("nope".to_string()).is_err());
+}
+
+#[test]
+fn or_else_sees_the_original_state() {
+    let step =
+        (|mut log: Vec<&'static str>| -> Result<(&'static str, Vec<&'static str>), &'static str> {
+            log.push("partial");
+            Err("failed")
+        })
+        .or_else(|error| move |log: Vec<&'static str>| Ok::<_, ()>((error, log)));
+
+    assert_eq!(
+        step.try_run(vec!["original"]),
+        Ok(("failed", vec!["original"]))
+    );
+}
+
+#[test]
+fn recover_replaces_the_failure() {
+    let step = (|_: u32| Err::<(u32, u32), _>("boom"))
+        .recover(|error, input| (error.len() as u32, input + 100));
+
+    assert_eq!(step.run(1), (4, 101));
+}
+
+#[test]
+fn recover_is_not_called_on_success() {
+    let called = Cell::new(false);
+    let step = (|n: u32| Ok::<_, &str>((n, n + 1))).recover(|_, input| {
+        called.set(true);
+        (0, input)
+    });
+
+    assert_eq!(step.run(1), (1, 2));
+    assert!(!called.get());
+}
+
+#[derive(Debug, PartialEq)]
+enum LedgerError {
+    Parse(ParseIntError),
+    Negative(i64),
+}
+
+fn parse_then_check() -> impl TryIndexedState<String, i64, u64, Error = LedgerError> {
+    let parse = (|text: String| text.parse::<i64>().map(|n| ((), n))).map_err(LedgerError::Parse);
+    let check = (|n: i64| {
+        if n < 0 {
+            Err(n)
+        } else {
+            Ok((n.unsigned_abs(), n))
+        }
+    })
+    .map_err(LedgerError::Negative);
+    parse.try_and_then(move |()| check)
+}
+
+#[test]
+fn map_err_unifies_different_errors() {
+    assert_eq!(parse_then_check().try_run("42".to_string()), Ok((42, 42)));
+    assert_eq!(
+        parse_then_check().try_run("-3".to_string()),
+        Err(LedgerError::Negative(-3))
+    );
+    assert!(matches!(
+        parse_then_check().try_run("x".to_string()),
+        Err(LedgerError::Parse(_))
+    ));
+}
+
+#[test]
+fn fail_and_ok_guard_a_value() {
+    #[derive(Debug, PartialEq)]
+    struct TooSmall;
+
+    let guard =
+        |n: i32| ok::<_, TooSmall>(n).try_and_then(|v| if v > 3 { ok(v) } else { fail(TooSmall) });
+
+    assert_eq!(guard(5).try_run("state"), Ok((5, "state")));
+    assert_eq!(guard(2).try_run("state"), Err(TooSmall));
+}
+
+#[test]
+fn retry_succeeds_on_the_third_attempt() {
+    let calls = Cell::new(0);
+    let step = retry(5, || {
+        calls.set(calls.get() + 1);
+        let attempt = calls.get();
+        move |n: u32| {
+            if attempt < 3 {
+                Err(attempt)
+            } else {
+                Ok((attempt, n + 1))
+            }
+        }
+    });
+
+    assert_eq!(step.try_run(1), Ok((3, 2)));
+    assert_eq!(calls.get(), 3);
+}
+
+#[test]
+fn retry_returns_the_last_error() {
+    let retried = Cell::new(Vec::new());
+    let step = retry(2, || |_: u32| Err::<((), u32), _>("down")).on_retry(
+        |error: &&'static str, attempt| {
+            let mut seen = retried.take();
+            seen.push((*error, attempt));
+            retried.set(seen);
+        },
+    );
+
+    assert_eq!(
+        step.try_run(1),
+        Err(RetryError {
+            attempts: 2,
+            error: "down"
+        })
+    );
+    assert_eq!(retried.take(), vec![("down", 1)]);
+}
+
+fn load() -> impl TryIndexedState<&'static str, u32, u32, Error = ParseIntError> {
+    (|n: u32| Ok::<_, ParseIntError>((n * 2, n)))
+        .try_contramap_state(|raw: &'static str| raw.parse::<u32>())
+}
+
+#[test]
+fn try_contramap_state_converts_the_input() {
+    assert_eq!(load().try_run("4"), Ok((8, 4)));
+    assert!(load().try_run("four").is_err());
+}
+
+#[test]
+fn try_contramap_state_composes_with_try_and_then() {
+    let step =
+        load().try_and_then(|doubled| move |n: u32| Ok::<_, ParseIntError>((doubled + n, n)));
+
+    assert_eq!(step.try_run("4"), Ok((12, 4)));
+}
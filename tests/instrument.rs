@@ -0,0 +1,38 @@
+#![cfg(feature = "tracing")]
+
+use indexed_state::*;
+use std::sync::{Arc, Mutex};
+use tracing::span::Id;
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Registry;
+
+#[derive(Clone, Default)]
+struct Entered(Arc<Mutex<Vec<String>>>);
+
+impl<S> Layer<S> for Entered
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_enter(&self, id: &Id, context: Context<'_, S>) {
+        let span = context.span(id).expect("entered span exists");
+        self.0.lock().unwrap().push(span.name().to_string());
+    }
+}
+
+#[test]
+fn instrumented_enters_span_once() {
+    let entered = Entered::default();
+    let subscriber = Registry::default().with(entered.clone());
+
+    let value = tracing::subscriber::with_default(subscriber, || {
+        gets(|n: u32| n + 1).instrumented("increment").run(1)
+    });
+
+    assert_eq!(value, (2, 2));
+    assert_eq!(
+        *entered.0.lock().unwrap(),
+        vec!["indexed_state".to_string()]
+    );
+}
@@ -0,0 +1,60 @@
+use indexed_state::*;
+use std::collections::VecDeque;
+
+#[test]
+fn iterate_yields_successive_states() {
+    let states: Vec<u32> = iterate(|n: u32| (NotClone, n + 1), 0).take(5).collect();
+
+    assert_eq!(states, vec![1, 2, 3, 4, 5]);
+}
+
+struct NotClone;
+
+#[test]
+fn values_generates_fibonacci_numbers() {
+    let mut fibonacci = values(|(a, b): (u64, u64)| (a, (b, a + b)), (0, 1));
+
+    let numbers: Vec<u64> = fibonacci.by_ref().take(10).collect();
+
+    assert_eq!(numbers, vec![0, 1, 1, 2, 3, 5, 8, 13, 21, 34]);
+    assert_eq!(fibonacci.into_state(), (55, 89));
+}
+
+fn apply_transaction(amount: i64) -> impl IndexedState<i64, i64, i64> {
+    state(move |balance: i64| (balance + amount, balance + amount))
+}
+
+#[test]
+fn scan_state_yields_running_totals() {
+    let mut totals = [100, -30, 45, -15]
+        .into_iter()
+        .scan_state(0, apply_transaction);
+
+    assert_eq!(totals.by_ref().take(2).collect::<Vec<_>>(), vec![100, 70]);
+    assert_eq!(*totals.state(), 70);
+    assert_eq!(totals.by_ref().collect::<Vec<_>>(), vec![115, 100]);
+    assert_eq!(totals.finish(), 100);
+}
+
+#[test]
+fn thread_matches_for_each_item() {
+    let events = [5, -2, 8, -1];
+    let record = |amount: i64| state(move |balance: i64| (balance, balance + amount));
+
+    let (before, balance): (Vec<_>, _) = events.into_iter().thread(0, record).collect_with_state();
+    let ((), replayed) = for_each_item(events, |amount| record(amount).map(drop)).run(0);
+
+    assert_eq!(before, vec![0, 5, 3, 11]);
+    assert_eq!(balance, replayed);
+}
+
+#[test]
+fn thread_collects_into_other_containers() {
+    let (collected, count): (VecDeque<_>, _) = "abc"
+        .chars()
+        .thread(0, |c| state(move |n: usize| ((n, c), n + 1)))
+        .collect_with_state();
+
+    assert_eq!(collected, VecDeque::from([(0, 'a'), (1, 'b'), (2, 'c')]));
+    assert_eq!(count, 3);
+}
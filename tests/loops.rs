@@ -0,0 +1,171 @@
+use indexed_state::*;
+use std::ops::ControlFlow;
+
+fn collatz() -> impl IndexedState<u64, u64, u64> {
+    gets(|n: u64| {
+        if n.is_multiple_of(2) {
+            n / 2
+        } else {
+            3 * n + 1
+        }
+    })
+}
+
+#[test]
+fn loop_while_runs_collatz_to_one() {
+    let step = loop_while(collatz, |n: &u64| *n != 1, 100);
+
+    assert_eq!(step.run(6), (Bounded::Finished(1), 1));
+}
+
+#[test]
+fn loop_while_stops_at_the_limit() {
+    let step = loop_while(collatz, |n: &u64| *n != 1, 3);
+
+    assert_eq!(step.run(6), (Bounded::Exceeded(5), 5));
+}
+
+#[test]
+#[should_panic(expected = "the limit of loop_while must be at least 1")]
+fn loop_while_rejects_zero_limit() {
+    loop_while(collatz, |n: &u64| *n != 1, 0);
+}
+
+#[derive(Clone, Copy)]
+enum Entry {
+    Deposit(i64),
+    Withdraw(i64),
+}
+
+fn post(entry: Entry) -> impl IndexedState<i64, i64, ()> {
+    modify(move |balance: i64| match entry {
+        Entry::Deposit(amount) => balance + amount,
+        Entry::Withdraw(amount) => balance - amount,
+    })
+}
+
+const LEDGER: [Entry; 4] = [
+    Entry::Deposit(100),
+    Entry::Withdraw(30),
+    Entry::Deposit(45),
+    Entry::Withdraw(15),
+];
+
+#[test]
+fn for_each_item_replays_a_ledger() {
+    assert_eq!(for_each_item(LEDGER, post).run(0), ((), 100));
+}
+
+#[test]
+fn for_each_item_without_items_leaves_state_untouched() {
+    assert_eq!(for_each_item([], post).run(5), ((), 5));
+}
+
+fn pop() -> impl IndexedState<Vec<u32>, Vec<u32>, Option<u32>> {
+    state(|mut queue: Vec<u32>| (queue.pop(), queue))
+}
+
+#[test]
+fn repeat_until_drains_the_queue() {
+    let step = repeat_until(pop, Vec::is_empty, 10);
+
+    assert_eq!(step.run(vec![1, 2, 3]), (Bounded::Finished(3), vec![]));
+}
+
+#[test]
+fn repeat_until_checks_before_the_first_run() {
+    let step = repeat_until(pop, Vec::is_empty, 10);
+
+    assert_eq!(step.run(vec![]), (Bounded::Finished(0), vec![]));
+}
+
+#[test]
+fn repeat_until_stops_at_the_limit() {
+    let step = repeat_until(pop, Vec::is_empty, 2);
+
+    assert_eq!(step.run(vec![1, 2, 3]), (Bounded::Exceeded(2), vec![1]));
+}
+
+#[test]
+fn loop_runs_a_million_iterations() {
+    let step = loop_(0u32, |count| {
+        state(move |total: u64| {
+            if count == 1_000_000 {
+                (ControlFlow::Break(count), total)
+            } else {
+                (ControlFlow::Continue(count + 1), total + 1)
+            }
+        })
+    });
+
+    assert_eq!(step.run(0), (1_000_000, 1_000_000));
+}
+
+#[test]
+fn for_each_chunked_matches_for_each_item() {
+    let add = |n: u32| modify(move |total: u32| total + n);
+    let mut chunks = Vec::new();
+
+    let chunked = for_each_chunked(1..=10, 4, add, |total: &u32, processed| {
+        chunks.push((*total, processed))
+    })
+    .run(0);
+
+    assert_eq!(chunked, for_each_item(1..=10, add).run(0));
+    assert_eq!(chunks, vec![(10, 4), (36, 8), (55, 10)]);
+}
+
+#[test]
+fn drain_until_returns_the_unconsumed_items() {
+    let step = drain_until(vec![3, 4, 0, 7, 8], |item: u32| {
+        state(move |total: u32| {
+            if item == 0 {
+                (ControlFlow::Break("terminator"), total)
+            } else {
+                (ControlFlow::Continue(()), total + item)
+            }
+        })
+    });
+
+    let ((done, remaining), total) = step.run(0);
+
+    assert_eq!((done, total), (Some("terminator"), 7));
+    assert_eq!(remaining.collect::<Vec<_>>(), vec![7, 8]);
+}
+
+#[test]
+fn drain_until_without_a_break_consumes_everything() {
+    let step = drain_until(vec![1, 2], |item: u32| {
+        modify(move |total: u32| total + item).map(|()| ControlFlow::<()>::Continue(()))
+    });
+
+    let ((done, mut remaining), total) = step.run(0);
+
+    assert_eq!((done, total), (None, 3));
+    assert!(remaining.next().is_none());
+}
+
+#[test]
+fn fold_events_rebuilds_an_aggregate() {
+    let events: Vec<i64> = (1..=10).map(|n| if n % 3 == 0 { -n } else { n }).collect();
+
+    let (effects, balance): (Vec<String>, i64) = fold_events(0, &events, |event: &&i64| {
+        let amount = **event;
+        state(move |balance: i64| {
+            let effects = (amount < 0).then(|| format!("withdrew {}", -amount));
+            (effects, balance + amount)
+        })
+    });
+
+    assert_eq!(balance, 19);
+    assert_eq!(effects, vec!["withdrew 3", "withdrew 6", "withdrew 9"]);
+}
+
+#[test]
+fn fold_events_without_events_returns_the_initial_state() {
+    let (effects, balance): (Vec<String>, i64) =
+        fold_events(5, Vec::<i64>::new(), |_: &i64| pure(Vec::<String>::new()));
+
+    assert!(effects.is_empty());
+    assert_eq!(balance, 5);
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "std")]
+
+use indexed_state::*;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+#[test]
+fn run_with_observer_records_labels_in_order() {
+    let labels = Rc::new(RefCell::new(Vec::new()));
+    let sink = Rc::clone(&labels);
+    let step = modify(|n: u32| n + 1)
+        .named("first")
+        .then(modify(|n: u32| n * 2).named("second"))
+        .then(gets(|n: u32| n - 1).named("third"));
+
+    let result = step.run_with_observer(1, move |label| sink.borrow_mut().push(label));
+
+    assert_eq!(result, (3, 3));
+    assert_eq!(*labels.borrow(), vec!["first", "second", "third"]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn timed_with_measures_with_the_injected_clock() {
+    let start = Instant::now();
+    let ticks = Cell::new(0);
+    let clock = || {
+        let tick = ticks.get();
+        ticks.set(tick + 1);
+        start + Duration::from_millis(250 * tick)
+    };
+
+    let ((value, elapsed), state) = gets(|n: u32| n + 1).timed_with(clock).run(1);
+
+    assert_eq!((value, state), (2, 2));
+    assert_eq!(elapsed, Duration::from_millis(250));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn memoized_calls_the_factory_once_per_input() {
+    let calls = Cell::new(0);
+    let mut memo = memoized(|| {
+        calls.set(calls.get() + 1);
+        gets(|n: u32| n * 2)
+    });
+
+    assert_eq!(memo.run_mut(2), (4, 4));
+    assert_eq!(memo.run_mut(2), (4, 4));
+    assert_eq!(memo.run_mut(3), (6, 6));
+    assert_eq!(calls.get(), 2);
+}
@@ -0,0 +1,64 @@
+use indexed_state::*;
+use std::cell::Cell;
+
+fn even_after_increment() -> impl IndexedState<u32, u32, Option<u32>> {
+    gets(|n: u32| n + 1).filter(|n| n.is_multiple_of(2))
+}
+
+#[test]
+fn filter_keeps_the_state_on_both_branches() {
+    assert_eq!(even_after_increment().run(1), (Some(2), 2));
+    assert_eq!(even_after_increment().run(2), (None, 3));
+}
+
+fn previous() -> impl IndexedState<u32, u32, Option<u32>> {
+    gots(|n: u32| n.checked_sub(1))
+}
+
+#[test]
+fn value_unwrap_or_handles_both_paths() {
+    assert_eq!(previous().value_unwrap_or(0).run(5), (4, 5));
+    assert_eq!(previous().value_unwrap_or(0).run(0), (0, 0));
+    assert_eq!(previous().value_unwrap_or_else(|| 9).run(0), (9, 0));
+}
+
+#[test]
+fn value_map_or_handles_both_paths() {
+    let describe = || previous().value_map_or("none".to_string(), |n| n.to_string());
+
+    assert_eq!(describe().run(5), ("4".to_string(), 5));
+    assert_eq!(describe().run(0), ("none".to_string(), 0));
+}
+
+#[test]
+fn ok_or_else_is_lazy_for_some() {
+    let called = Cell::new(false);
+    let step = gots(|n: u32| Some(n)).ok_or_else(|| {
+        called.set(true);
+        "missing"
+    });
+
+    assert_eq!(step.run(1), (Ok(1), 1));
+    assert!(!called.get());
+}
+
+#[test]
+fn ok_or_uses_the_error_for_none() {
+    let step = gots(|_: u32| None::<u32>).ok_or("missing");
+
+    assert_eq!(step.run(1), (Err("missing"), 1));
+}
+
+#[test]
+fn lift_option_runs_some() {
+    let step = lift_option(Some(gets(|n: u32| n + 1))).and_then(|found| pure(found.is_some()));
+
+    assert_eq!(step.run(1), (true, 2));
+}
+
+#[test]
+fn lift_option_skips_none() {
+    let step = run_if(false, gets(|n: u32| n + 1)).and_then(|found| pure(found.is_some()));
+
+    assert_eq!(step.run(1), (false, 1));
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "rayon")]
+
+use indexed_state::*;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+fn count(word: &'static str) -> impl IndexedState<usize, usize, usize> {
+    state(move |seen: usize| (word.len(), seen + word.len()))
+}
+
+#[test]
+fn par_traverse_matches_traverse() {
+    let words = vec!["one", "three", "five", "seven"];
+
+    let parallel = par_traverse(words.clone(), count).run(vec![0, 10, 20, 30]);
+    let pairs = words.into_iter().zip(vec![0, 10, 20, 30]);
+    let sequential: (Vec<_>, Vec<_>) = pairs.map(|(word, seen)| count(word).run(seen)).unzip();
+
+    assert_eq!(parallel, sequential);
+}
+
+#[test]
+fn par_traverse_runs_on_several_threads() {
+    let threads = Mutex::new(HashSet::new());
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+
+    pool.install(|| {
+        par_traverse((0..8).collect(), |item: u32| {
+            let threads = &threads;
+            state(move |sub: u32| {
+                threads.lock().unwrap().insert(thread::current().id());
+                thread::sleep(Duration::from_millis(20));
+                ((), sub + item)
+            })
+        })
+        .run(vec![0; 8])
+    });
+
+    assert!(threads.into_inner().unwrap().len() > 1);
+}
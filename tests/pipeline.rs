@@ -0,0 +1,29 @@
+use indexed_state::*;
+
+#[test]
+fn pipeline_runs_twice() {
+    let mut pipeline = Pipeline::new(|| gets(|n: u32| n + 1).map(|n| n * 2));
+
+    assert_eq!(pipeline.run(1), (4, 2));
+    assert_eq!(pipeline.run(5), (12, 6));
+}
+
+#[test]
+fn pipeline_runner_runs_every_state() {
+    let mut pipeline = Pipeline::new(|| modify(|n: u32| n + 1));
+
+    let results: Vec<_> = [1, 2, 3].into_iter().map(pipeline.runner()).collect();
+
+    assert_eq!(results, vec![((), 2), ((), 3), ((), 4)]);
+}
+
+#[test]
+fn pipelines_compose_with_then() {
+    let increment = Pipeline::new(|| modify(|n: u32| n + 1));
+    let describe = Pipeline::new(|| state(|n: u32| (n, n.to_string())));
+
+    let mut both = increment.then(describe);
+
+    assert_eq!(both.run(1), (2, "2".to_string()));
+    assert_eq!(both.run(9), (10, "10".to_string()));
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "alloc")]
+
+use indexed_state::*;
+
+fn damped_oscillator(_: usize) -> impl IndexedState<(f64, f64), (f64, f64), f64> {
+    state(|(position, velocity): (f64, f64)| {
+        let (sin, cos) = 0.3f64.sin_cos();
+        let position_next = 0.95 * (position * cos + velocity * sin);
+        let velocity_next = 0.95 * (velocity * cos - position * sin);
+        let magnitude = position_next.hypot(velocity_next);
+        (magnitude, (position_next, velocity_next))
+    })
+}
+
+#[test]
+fn accumulate_collects_damped_oscillator_magnitudes() {
+    let (values, _) = accumulate(damped_oscillator, 100).run((1.0, 0.0));
+
+    assert_eq!(values.len(), 100);
+    assert!(values.windows(2).all(|pair| pair[1].abs() < pair[0].abs()));
+}
+
+#[test]
+fn accumulate_zero_times_leaves_state_untouched() {
+    let (values, state) = accumulate(damped_oscillator, 0).run((1.0, 0.0));
+
+    assert!(values.is_empty());
+    assert_eq!(state, (1.0, 0.0));
+}
+
+#[test]
+fn history_collects_every_state() {
+    let (states, state) = history(|| modify(|n: u32| n * 2), 5).run(1);
+
+    assert_eq!(states, vec![2, 4, 8, 16, 32]);
+    assert_eq!(states.last(), Some(&state));
+}
+
+fn increment(_: usize) -> impl IndexedState<u32, u32, u32> {
+    state(|n: u32| (n, n + 1))
+}
+
+#[test]
+fn repeat_increments_a_thousand_times() {
+    let (values, state) = repeat(increment, 1000).run(0);
+
+    assert_eq!(state, 1000);
+    assert_eq!(values, (0..1000).collect::<Vec<_>>());
+}
+
+#[test]
+fn repeat_fold_sums_a_thousand_increments() {
+    let step = repeat_fold(increment, 1000, 0u64, |sum, n| sum + u64::from(n));
+
+    assert_eq!(step.run(0), (499_500, 1000));
+}
+
+#[test]
+fn repeat_fold_zero_times_returns_the_initial_accumulator() {
+    let step = repeat_fold(increment, 0, 7u64, |sum, n| sum + u64::from(n));
+
+    assert_eq!(step.run(3), (7, 3));
+}
@@ -0,0 +1,58 @@
+use indexed_state::*;
+use std::cell::RefCell;
+
+#[test]
+fn try_evaluate_returns_the_result_value() {
+    let ok = gots(|n: u32| Ok::<_, &str>(n + 1));
+    let err = gots(|_: u32| Err::<u32, _>("nope"));
+
+    assert_eq!(ok.try_evaluate(1), Ok(2));
+    assert_eq!(err.try_evaluate(1), Err("nope"));
+}
+
+#[test]
+fn try_execute_drops_the_state_on_error() {
+    let ok = modify(|n: u32| n + 1).map(|_| Ok::<_, &str>(()));
+    let err = modify(|n: u32| n + 1).map(|_| Err::<(), _>("nope"));
+
+    assert_eq!(ok.try_execute(1), Ok(2));
+    assert_eq!(err.try_execute(1), Err("nope"));
+}
+
+#[test]
+fn select_only_constructs_the_taken_arm() {
+    let constructed = RefCell::new(Vec::new());
+    let log = &constructed;
+    let charge = |amount: Result<u32, &'static str>| {
+        gots(move |_: u32| amount).select(
+            move |n| {
+                log.borrow_mut().push("ok");
+                modify(move |balance: u32| balance + n)
+            },
+            move |_| {
+                log.borrow_mut().push("err");
+                put(0)
+            },
+        )
+    };
+
+    assert_eq!(charge(Ok(2)).run(1), ((), 3));
+    assert_eq!(*constructed.borrow(), vec!["ok"]);
+    assert_eq!(charge(Err("declined")).run(1), ((), 0));
+    assert_eq!(*constructed.borrow(), vec!["ok", "err"]);
+}
+
+fn charge(approved: bool) -> impl IndexedState<u32, u32, Result<u32, &'static str>> {
+    gots(move |_: u32| if approved { Ok(5) } else { Err("declined") })
+        .bind_ok(|amount| gets(move |n: u32| n + amount).map(Ok))
+}
+
+#[test]
+fn bind_ok_continues_on_ok() {
+    assert_eq!(charge(true).run(1), (Ok(6), 6));
+}
+
+#[test]
+fn bind_ok_skips_the_continuation_on_err() {
+    assert_eq!(charge(false).run(1), (Err("declined"), 1));
+}
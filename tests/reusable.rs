@@ -0,0 +1,55 @@
+use indexed_state::*;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn composed_mut_pipeline_runs_against_three_inputs() {
+    let mut calls = 0;
+    let mut pipeline = (move |n: u32| {
+        calls += 1;
+        (calls, n + 1)
+    })
+    .map_mut(|calls: u32| calls * 10)
+    .map_state_mut(|n: u32| n * 2);
+
+    assert_eq!(pipeline.run_mut(1), (10, 4));
+    assert_eq!(pipeline.run_mut(2), (20, 6));
+    assert_eq!(pipeline.run_mut(3), (30, 8));
+}
+
+#[test]
+fn by_mut_runs_a_mut_pipeline_once() {
+    let mut step = |n: u32| (n, n + 1);
+
+    let ((), state) = step.by_mut().and_then(|n| put(n * 100)).run(1);
+
+    assert_eq!(state, 100);
+    assert_eq!(step.run_mut(5), (5, 6));
+}
+
+#[test]
+fn arc_pipeline_runs_from_two_threads() {
+    let pipeline = Arc::new((|n: u32| (n, n + 1)).map_ref(|n: u32| n * 2));
+
+    let handles: Vec<_> = [1, 2]
+        .into_iter()
+        .map(|input| {
+            let pipeline = Arc::clone(&pipeline);
+            thread::spawn(move || pipeline.run_ref(input))
+        })
+        .collect();
+    let results: Vec<_> = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+
+    assert_eq!(results, vec![(2, 2), (4, 3)]);
+}
+
+#[test]
+fn by_ref_runs_a_ref_pipeline_without_consuming_it() {
+    let step = |n: u32| (n, n + 1);
+
+    assert_eq!(step.by_ref().map(|n| n + 10).run(1), (11, 2));
+    assert_eq!(step.run_ref(2), (2, 3));
+}
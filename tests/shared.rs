@@ -0,0 +1,27 @@
+#![cfg(feature = "std")]
+
+use indexed_state::*;
+use std::collections::HashMap;
+
+#[test]
+fn shared_transition_runs_under_two_keys() {
+    let transition = shared(|n: u32| (n * 2, n + 1));
+    let mut routes = HashMap::new();
+    routes.insert("double", transition.clone());
+    routes.insert("also_double", transition);
+
+    assert_eq!(routes["double"].clone().run(1), (2, 2));
+    assert_eq!(routes["also_double"].clone().run(3), (6, 4));
+    assert_eq!(routes["double"].run_ref(5), (10, 6));
+}
+
+#[test]
+fn arc_shared_runs_on_another_thread() {
+    let transition = arc_shared(|n: u32| (n * 2, n + 1));
+    let remote = transition.clone();
+
+    let handle = std::thread::spawn(move || remote.run(1));
+
+    assert_eq!(handle.join().unwrap(), (2, 2));
+    assert_eq!(transition.run(2), (4, 3));
+}
@@ -0,0 +1,161 @@
+#![cfg(feature = "alloc")]
+
+use indexed_state::*;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+
+#[test]
+fn try_sequence_stops_at_the_failing_index() {
+    let ran = RefCell::new(Vec::new());
+    let log = &ran;
+    let steps: Vec<_> = (0..4)
+        .map(|index| {
+            move |n: u32| -> Result<(usize, u32), &'static str> {
+                log.borrow_mut().push(index);
+                if index == 2 {
+                    Err("bad")
+                } else {
+                    Ok((index, n + 1))
+                }
+            }
+        })
+        .collect();
+
+    let result = try_sequence(steps).try_run(0);
+
+    assert_eq!(
+        result,
+        Err(ItemError {
+            index: 2,
+            error: "bad"
+        })
+    );
+    assert_eq!(*ran.borrow(), vec![0, 1, 2]);
+}
+
+fn odd_items_fail() -> Vec<impl TryIndexedState<u32, u32, u32, Error = u32>> {
+    (0..4u32)
+        .map(|index| {
+            move |n: u32| {
+                if !index.is_multiple_of(2) {
+                    Err(index)
+                } else {
+                    Ok((index, n + 10))
+                }
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn validate_all_reports_every_failure_and_keeps_successes() {
+    assert_eq!(validate_all(odd_items_fail()).run(0), (Err(vec![1, 3]), 20));
+}
+
+#[test]
+fn validate_all_isolated_reports_every_failure_and_keeps_nothing() {
+    assert_eq!(
+        validate_all_isolated(odd_items_fail()).run(0),
+        (Err(vec![1, 3]), 0)
+    );
+}
+
+#[test]
+fn traverse_assigns_incrementing_ids() {
+    let step = traverse(["ann", "bob", "cy"], |name| {
+        state(move |id: u32| ((id, name), id + 1))
+    });
+
+    assert_eq!(step.run(0), (vec![(0, "ann"), (1, "bob"), (2, "cy")], 3));
+}
+
+#[test]
+fn traverse_without_items_leaves_state_untouched() {
+    let step = traverse(Vec::<&str>::new(), |name| {
+        state(move |id: u32| ((id, name), id + 1))
+    });
+
+    assert_eq!(step.run(7), (vec![], 7));
+}
+
+#[test]
+fn sequence_runs_queued_transitions_in_order() {
+    let mut queue = Vec::new();
+    for amount in 1..=3 {
+        queue.push(state(move |n: u32| (n + amount, n + amount)));
+    }
+
+    assert_eq!(sequence(queue).run(0), (vec![1, 3, 6], 6));
+}
+
+#[test]
+fn sequence_without_transitions_leaves_state_untouched() {
+    let queue: Vec<fn(u32) -> ((), u32)> = Vec::new();
+
+    assert_eq!(sequence(queue).run(4), (vec![], 4));
+}
+
+#[test]
+fn traverse_map_replaces_values_and_keeps_keys() {
+    let stock = BTreeMap::from([("apples", 3), ("pears", 5)]);
+
+    let step = traverse_map(stock, |name: &&str, count: u32| {
+        let label = name.len();
+        state(move |total: u32| (count * 2 + label as u32, total + count))
+    });
+
+    assert_eq!(
+        step.run(0),
+        (BTreeMap::from([("apples", 12), ("pears", 15)]), 8)
+    );
+}
+
+#[test]
+fn partition_results_keeps_indices_of_failures() {
+    let step = partition_results(["1", "x", "3", "y"], |text: &str| {
+        state(move |attempts: u32| (text.parse::<u32>().map_err(|_| text), attempts + 1))
+    });
+
+    assert_eq!(step.run(0), ((vec![1, 3], vec![(1, "x"), (3, "y")]), 4));
+}
+
+#[test]
+fn pairwise_computes_deltas_and_statistics() {
+    let step = pairwise([10, 13, 11, 18], |previous: i32, next: i32| {
+        state(move |(count, largest): (u32, i32)| {
+            let delta = next - previous;
+            (delta, (count + 1, largest.max(delta)))
+        })
+    });
+
+    assert_eq!(step.run((0, i32::MIN)), (vec![3, -2, 7], (3, 7)));
+}
+
+#[test]
+fn pairwise_with_one_item_leaves_state_untouched() {
+    let step = pairwise([10], |previous: i32, next: i32| {
+        state(move |n: u32| (next - previous, n + 1))
+    });
+
+    assert_eq!(step.run(0), (vec![], 0));
+}
+
+#[test]
+fn traverse_control_breaks_on_the_third_item() {
+    let calls = Cell::new(0);
+    let step = traverse_control([1, 2, -3, 4, 5], |item: i32| {
+        calls.set(calls.get() + 1);
+        state(move |total: i32| {
+            if item < 0 {
+                (ControlFlow::Break(item), total)
+            } else {
+                (ControlFlow::Continue(item * 10), total + item)
+            }
+        })
+    });
+
+    assert_eq!(step.run(0), ((vec![10, 20], Some(-3)), 3));
+    assert_eq!(calls.get(), 3);
+}
@@ -0,0 +1,5 @@
+use indexed_state::{gets, pure, IndexedState};
+
+fn main() {
+    let _ = pure::<u8, _>(|n: u8| n + 1).apply(gets(|text: String| text.len() as u8));
+}
@@ -0,0 +1,5 @@
+use indexed_state::{pure, IndexedState};
+
+fn main() {
+    let _ = pure::<u8, _>(1u8).apply(pure(2u8));
+}
@@ -0,0 +1,26 @@
+#![cfg(feature = "std")]
+
+use indexed_state::*;
+use std::panic::UnwindSafe;
+
+fn panics_on_zero() -> impl IndexedState<u32, u32, ()> + UnwindSafe {
+    modify(|n: u32| if n == 0 { panic!("zero state") } else { n })
+}
+
+#[test]
+fn catch_panic_converts_the_panic_into_an_error() {
+    let error = panics_on_zero().catch_panic().try_run(0).unwrap_err();
+
+    assert_eq!(error.message, "zero state");
+    assert_eq!(panics_on_zero().catch_panic().try_run(1), Ok(((), 1)));
+}
+
+#[test]
+fn catch_panic_with_input_returns_the_input() {
+    let error = panics_on_zero()
+        .catch_panic_with_input()
+        .try_run(0)
+        .unwrap_err();
+
+    assert_eq!((error.message.as_str(), error.input), ("zero state", 0));
+}